@@ -0,0 +1,91 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { RedstoneSol } from "../target/types/redstone_sol";
+
+const makeFeedIdBytes = (feedId: string) => {
+  return Buffer.from(feedId.padEnd(32, "\0"));
+};
+
+const makePriceSeed = () => {
+  return Buffer.from("price".padEnd(32, "\0"));
+};
+
+// Upgrades the config, the given price accounts and their feed configs to
+// the current layout
+async function migrateAccounts(feedIds: string[]) {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.RedstoneSol as Program<RedstoneSol>;
+
+  const configAccount = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  )[0];
+  const systemProgram = anchor.web3.SystemProgram.programId;
+
+  try {
+    const tx = await program.methods
+      .migrateConfig()
+      .accountsStrict({
+        owner: provider.wallet.publicKey,
+        configAccount,
+        systemProgram,
+      })
+      .rpc();
+    console.log("Config migrated:", tx);
+  } catch (error) {
+    console.log("Config not migrated:", error.toString());
+  }
+
+  for (const feedId of feedIds) {
+    const priceAccount = anchor.web3.PublicKey.findProgramAddressSync(
+      [makePriceSeed(), makeFeedIdBytes(feedId)],
+      program.programId
+    )[0];
+    try {
+      const tx = await program.methods
+        .migratePriceAccount([Array.from(makeFeedIdBytes(feedId))])
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          priceAccount,
+          systemProgram,
+        })
+        .rpc();
+      console.log(`${feedId} migrated: ${tx}`);
+    } catch (error) {
+      console.log(`${feedId} not migrated: ${error.toString()}`);
+    }
+
+    const feedConfig = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("feed_config"), makeFeedIdBytes(feedId)],
+      program.programId
+    )[0];
+    if (!(await provider.connection.getAccountInfo(feedConfig))) {
+      continue;
+    }
+    try {
+      const tx = await program.methods
+        .migrateFeedConfig([Array.from(makeFeedIdBytes(feedId))])
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          feedConfig,
+          systemProgram,
+        })
+        .rpc();
+      console.log(`${feedId} feed config migrated: ${tx}`);
+    } catch (error) {
+      console.log(`${feedId} feed config not migrated: ${error.toString()}`);
+    }
+  }
+}
+
+// Feed ids to migrate, e.g. `ts-node migrations/migrate-accounts.ts BTC ETH`
+migrateAccounts(process.argv.slice(2))
+  .then(() => process.exit(0))
+  .catch((error) => {
+    console.error(error);
+    process.exit(1);
+  });
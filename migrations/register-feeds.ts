@@ -0,0 +1,78 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { RedstoneSol } from "../target/types/redstone_sol";
+
+async function registerFeeds(feedIds: string[]) {
+  // Local env by default, ammend Anchor.toml for prod
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.RedstoneSol as Program<RedstoneSol>;
+
+  const configAccount = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  )[0];
+  const feedRegistry = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("registry")],
+    program.programId
+  )[0];
+
+  const accounts = {
+    owner: provider.wallet.publicKey,
+    configAccount,
+    feedRegistry,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  };
+
+  if (!(await provider.connection.getAccountInfo(feedRegistry))) {
+    await program.methods.initializeFeedRegistry().accountsStrict(accounts).rpc();
+    console.log("Feed registry initialized at:", feedRegistry.toString());
+  }
+
+  const registry = await program.account.feedRegistry.fetch(feedRegistry);
+  const registered = new Set(
+    registry.feeds.map((feed) =>
+      Buffer.from(feed.feedId[0]).toString("utf8").replace(/\0+$/, "")
+    )
+  );
+
+  for (const feedId of feedIds) {
+    if (registered.has(feedId)) {
+      console.log(`${feedId} already registered`);
+      continue;
+    }
+    const feedIdBytes = Buffer.from(feedId.padEnd(32, "\0"));
+    const tx = await program.methods
+      .registerFeed(feedId)
+      .accountsStrict({
+        ...accounts,
+        // checked to be unused by a derived or basket feed
+        derivedFeed: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("derived_feed"), feedIdBytes],
+          program.programId
+        )[0],
+        basketFeed: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("basket_feed"), feedIdBytes],
+          program.programId
+        )[0],
+      })
+      .rpc();
+    console.log(`Registered ${feedId}: ${tx}`);
+  }
+}
+
+// Feed ids to register, e.g. `ts-node migrations/register-feeds.ts BTC ETH`
+const feedIds = process.argv.slice(2);
+
+if (feedIds.length === 0) {
+  console.error("Please provide at least one feed id to register");
+  process.exit(1);
+}
+
+registerFeeds(feedIds)
+  .then(() => process.exit(0))
+  .catch((error) => {
+    console.error(error);
+    process.exit(1);
+  });
@@ -32,6 +32,7 @@ async function updateSignerThreshold(newThreshold: number) {
       .accountsStrict({
         owner: provider.wallet.publicKey,
         configAccount,
+        systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
 
@@ -33,6 +33,7 @@ async function updateSigners() {
       .accountsStrict({
         owner: provider.wallet.publicKey,
         configAccount,
+        systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
 
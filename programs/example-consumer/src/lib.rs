@@ -0,0 +1,56 @@
+//! Template for programs consuming RedStone prices: the price account is
+//! loaded directly and verified by `redstone_sol::consumer`, no CPI needed.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use redstone_sol::consumer::load_price;
+use redstone_sol::state::FeedId;
+
+declare_id!("Bqmk3vKiCdKejTVPEfoNPKBjhSMrPTeZ84KnEJmx7kZ6");
+
+/// Decimals of the collateral valuation
+pub const VALUE_DECIMALS: u8 = 8;
+
+#[program]
+pub mod example_consumer {
+    use super::*;
+
+    /// Values `amount` of collateral at the current price, the result is
+    /// returned as little-endian u128 with `VALUE_DECIMALS`
+    pub fn value_collateral(
+        ctx: Context<ValueCollateral>,
+        feed_id: FeedId,
+        amount: u64,
+        max_age_ms: u64,
+    ) -> Result<()> {
+        let price =
+            load_price(&ctx.accounts.price_account, &feed_id, max_age_ms)?;
+        let value = collateral_value(price.to_u128(VALUE_DECIMALS)?, amount)?;
+        msg!(
+            "{} units valued at {} (price {} at {})",
+            amount,
+            value,
+            price.to_decimal_string(),
+            price.timestamp
+        );
+        set_return_data(&value.to_le_bytes());
+        Ok(())
+    }
+}
+
+pub fn collateral_value(price: u128, amount: u64) -> Result<u128> {
+    price
+        .checked_mul(amount as u128)
+        .ok_or(ConsumerError::ValuationOverflow.into())
+}
+
+#[derive(Accounts)]
+pub struct ValueCollateral<'info> {
+    /// CHECK: address, owner and layout are verified by `load_price`
+    pub price_account: UncheckedAccount<'info>,
+}
+
+#[error_code]
+pub enum ConsumerError {
+    #[msg("Collateral valuation overflow")]
+    ValuationOverflow,
+}
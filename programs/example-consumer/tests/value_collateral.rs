@@ -0,0 +1,271 @@
+//! Pushes a signed payload through `redstone-sol`, then values collateral
+//! with the resulting price account.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::{signature::Signer, transaction::Transaction};
+
+use redstone_sol::instructions::make_price_seed;
+use redstone_sol::state::FeedId;
+
+/// Signed BTC payload with three data packages
+const PAYLOAD: &[u8] = &[
+    66, 84, 67, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 58, 182, 116, 104, 177, 1, 146, 170,
+    223, 139, 176, 0, 0, 0, 32, 0, 0, 1, 43, 218, 184, 99, 113, 194, 156,
+    200, 231, 35, 84, 134, 87, 215, 8, 159, 159, 138, 105, 210, 213, 205,
+    124, 73, 234, 227, 40, 9, 226, 13, 146, 163, 92, 252, 93, 106, 169, 6,
+    115, 168, 226, 166, 112, 111, 90, 237, 27, 253, 190, 225, 47, 16, 208,
+    114, 14, 12, 235, 44, 110, 244, 188, 96, 6, 91, 28, 66, 84, 67, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 6, 58, 182, 116, 104, 177, 1, 146, 170, 223, 139, 176, 0, 0,
+    0, 32, 0, 0, 1, 143, 215, 175, 236, 103, 162, 86, 18, 42, 103, 87, 163,
+    21, 179, 144, 222, 90, 247, 211, 241, 62, 242, 230, 233, 83, 191, 182,
+    114, 36, 143, 211, 94, 101, 30, 185, 70, 46, 86, 232, 98, 31, 250, 231,
+    52, 221, 240, 205, 18, 253, 240, 185, 77, 77, 238, 224, 181, 19, 123,
+    123, 165, 5, 160, 214, 87, 28, 66, 84, 67, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 58,
+    182, 131, 169, 123, 1, 146, 170, 223, 139, 176, 0, 0, 0, 32, 0, 0, 1,
+    177, 208, 87, 179, 126, 42, 24, 134, 220, 94, 31, 20, 23, 243, 240, 182,
+    150, 195, 62, 93, 178, 128, 110, 81, 78, 111, 213, 245, 165, 208, 42,
+    154, 46, 93, 3, 148, 58, 109, 254, 91, 37, 223, 99, 96, 105, 2, 113, 158,
+    31, 135, 46, 69, 135, 94, 17, 144, 2, 128, 255, 253, 252, 53, 221, 89,
+    27, 0, 3, 0, 0, 0, 0, 0, 2, 237, 87, 1, 30, 0, 0,
+];
+
+const MAX_AGE_MS: u64 = 60_000;
+
+// Anchor entrypoints tie the account slice and account lifetimes together,
+// which `processor!` does not allow for
+fn redstone_sol_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    redstone_sol::entry(program_id, accounts, data)
+}
+
+fn example_consumer_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    example_consumer::entry(program_id, accounts, data)
+}
+
+fn btc_feed_id() -> FeedId {
+    "BTC".parse().unwrap()
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &redstone_sol::ID).0
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+) -> std::result::Result<(), solana_program_test::BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+async fn set_clock_ms(context: &mut ProgramTestContext, timestamp_ms: u64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = (timestamp_ms / 1000) as i64;
+    context.set_sysvar(&clock);
+}
+
+/// Sets up `redstone-sol` with the payload signers and registers BTC
+async fn start() -> (ProgramTestContext, u64) {
+    let mut program_test = ProgramTest::new(
+        "redstone_sol",
+        redstone_sol::ID,
+        processor!(redstone_sol_entry),
+    );
+    program_test.add_program(
+        "example_consumer",
+        example_consumer::ID,
+        processor!(example_consumer_entry),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let payload =
+        redstone_sol::redstone::parse_raw_payload(&mut PAYLOAD.to_vec())
+            .unwrap();
+    let signers = payload
+        .data_packages
+        .iter()
+        .map(|package| package.signer_address)
+        .collect();
+    let package_timestamp = payload.data_packages[0].timestamp;
+    set_clock_ms(&mut context, package_timestamp).await;
+
+    let owner = context.payer.pubkey();
+    let feed_id = btc_feed_id();
+    let config_account = pda(&[b"config"]);
+    let feed_registry = pda(&[b"registry"]);
+
+    let instructions = [
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::Initialize {
+                owner,
+                config_account,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::Initialize {
+                signers,
+                signer_count_threshold: 3,
+                max_timestamp_delay_ms: 15 * 60 * 1000,
+                max_timestamp_ahead_ms: 3 * 60 * 1000,
+            }
+            .data(),
+        },
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::InitializeFeedRegistry {
+                owner,
+                config_account,
+                feed_registry,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::InitializeFeedRegistry {}.data(),
+        },
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::RegisterFeed {
+                owner,
+                config_account,
+                feed_registry,
+                derived_feed: pda(&[b"derived_feed", feed_id.as_ref()]),
+                basket_feed: pda(&[b"basket_feed", feed_id.as_ref()]),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::RegisterFeed {
+                name: feed_id.to_string(),
+            }
+            .data(),
+        },
+    ];
+    for instruction in instructions {
+        send(&mut context, instruction).await.unwrap();
+    }
+
+    (context, package_timestamp)
+}
+
+fn process_payload_instruction(owner: Pubkey) -> Instruction {
+    let feed_id = btc_feed_id();
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::ProcessPayload {
+            user: owner,
+            price_account: pda(&[&make_price_seed(), feed_id.as_ref()]),
+            config_account: pda(&[b"config"]),
+            feed_config: pda(&[b"feed_config", feed_id.as_ref()]),
+            feed_registry: pda(&[b"registry"]),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::ProcessRedstonePayload {
+            feed_id,
+            payload: PAYLOAD.to_vec(),
+        }
+        .data(),
+    }
+}
+
+/// Sets up `redstone-sol` with the payload signers and pushes the payload
+async fn setup() -> (ProgramTestContext, u64) {
+    let (mut context, package_timestamp) = start().await;
+    let owner = context.payer.pubkey();
+    send(&mut context, process_payload_instruction(owner))
+        .await
+        .unwrap();
+    (context, package_timestamp)
+}
+
+fn value_collateral_instruction(amount: u64) -> Instruction {
+    let feed_id = btc_feed_id();
+    Instruction {
+        program_id: example_consumer::ID,
+        accounts: example_consumer::accounts::ValueCollateral {
+            price_account: pda(&[&make_price_seed(), feed_id.as_ref()]),
+        }
+        .to_account_metas(None),
+        data: example_consumer::instruction::ValueCollateral {
+            feed_id,
+            amount,
+            max_age_ms: MAX_AGE_MS,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+async fn test_value_collateral() {
+    let (mut context, _) = setup().await;
+
+    let price_account = context
+        .banks_client
+        .get_account(pda(&[&make_price_seed(), btc_feed_id().as_ref()]))
+        .await
+        .unwrap()
+        .unwrap();
+    let price =
+        u128::from_be_bytes(price_account.data[56..72].try_into().unwrap());
+    assert!(price > 0);
+
+    let blockhash =
+        context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[value_collateral_instruction(3)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(simulation.result.unwrap().is_ok());
+
+    let return_data =
+        simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, example_consumer::ID);
+    assert_eq!(
+        u128::from_le_bytes(return_data.data.try_into().unwrap()),
+        price * 3
+    );
+}
+
+#[tokio::test]
+async fn test_value_collateral_rejects_stale_price() {
+    let (mut context, package_timestamp) = setup().await;
+    set_clock_ms(&mut context, package_timestamp + MAX_AGE_MS + 1000).await;
+
+    let err = send(&mut context, value_collateral_instruction(3))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains(&format!(
+        "{:#x}",
+        u32::from(redstone_sol::error::RedstoneError::StalePrice)
+    )));
+}
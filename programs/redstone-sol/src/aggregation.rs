@@ -0,0 +1,354 @@
+//! Aggregation of the values signed for a feed.
+use anchor_lang::prelude::*;
+use zkp_u256::U256;
+
+use crate::constants::BPS_DENOMINATOR;
+use crate::util::{deviation_bps, median};
+
+/// Aggregation of the signed values, chosen per feed in `FeedConfig`
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+)]
+pub enum Aggregation {
+    /// Middle value, the average of the two middle ones for even counts
+    #[default]
+    Median,
+    /// Lower of the two middle values for even counts
+    LowerMedian,
+    /// Upper of the two middle values for even counts
+    UpperMedian,
+    /// Mean after dropping `trim_bps` of the values from each end
+    TrimmedMean { trim_bps: u16 },
+    /// Lowest value, conservative for collateral pricing
+    Min,
+    /// Highest value, conservative for debt pricing
+    Max,
+}
+
+impl Aggregation {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Aggregation::TrimmedMean { trim_bps } => {
+                (*trim_bps as u64) * 2 < BPS_DENOMINATOR
+            }
+            _ => true,
+        }
+    }
+
+    /// `None` for no values
+    pub fn aggregate(&self, values: &[U256]) -> Option<U256> {
+        match self {
+            Aggregation::Median => median(values),
+            Aggregation::LowerMedian => lower_median(values),
+            Aggregation::UpperMedian => upper_median(values),
+            Aggregation::TrimmedMean { trim_bps } => {
+                trimmed_mean(values, *trim_bps)
+            }
+            Aggregation::Min => values.iter().min().cloned(),
+            Aggregation::Max => values.iter().max().cloned(),
+        }
+    }
+}
+
+/// Timestamp stored for payloads whose packages were signed at different
+/// times, within `FeedConfig::max_package_timestamp_skew_ms`
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+)]
+pub enum TimestampAggregation {
+    /// Oldest package timestamp
+    #[default]
+    Min,
+    /// Lower median of the package timestamps, the timestamp of an actual
+    /// package for even counts too
+    Median,
+}
+
+impl TimestampAggregation {
+    /// `None` for no timestamps
+    pub fn aggregate(&self, timestamps: &[u64]) -> Option<u64> {
+        let mut timestamps = timestamps.to_vec();
+        timestamps.sort_unstable();
+        match self {
+            TimestampAggregation::Min => timestamps.first().copied(),
+            TimestampAggregation::Median => timestamps
+                .get(timestamps.len().checked_sub(1)? / 2)
+                .copied(),
+        }
+    }
+}
+
+/// Filter dropping the values too far from the median of all of them,
+/// applied before the aggregation
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum OutlierFilter {
+    /// Drops values deviating from the median by more than
+    /// `max_deviation_bps`
+    Band { max_deviation_bps: u64 },
+    /// Drops values deviating from the median by more than
+    /// `max_deviation_mads` median absolute deviations. Nothing is dropped
+    /// while the MAD is zero, with most of the values equal
+    Mad { max_deviation_mads: u8 },
+}
+
+impl OutlierFilter {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            OutlierFilter::Band { max_deviation_bps } => {
+                *max_deviation_bps > 0
+            }
+            OutlierFilter::Mad { max_deviation_mads } => {
+                *max_deviation_mads > 0
+            }
+        }
+    }
+
+    /// Marks the values to keep, `None` for no values
+    pub fn keep(&self, values: &[U256]) -> Option<Vec<bool>> {
+        let center = median(values)?;
+        let keep = match self {
+            OutlierFilter::Band { max_deviation_bps } => values
+                .iter()
+                .map(|value| match deviation_bps(&center, value) {
+                    Some(deviation) => deviation <= *max_deviation_bps,
+                    None => value.is_zero(),
+                })
+                .collect(),
+            OutlierFilter::Mad { max_deviation_mads } => {
+                let deviations: Vec<U256> = values
+                    .iter()
+                    .map(|value| abs_diff(&center, value))
+                    .collect();
+                let mad = median(&deviations)?;
+                // a zero MAD would drop every value off the median, and at
+                // most 8 bits are left for the u8 multiplier
+                if mad.is_zero() || mad.bits() > 248 {
+                    return Some(vec![true; values.len()]);
+                }
+                let max_deviation =
+                    mad * U256::from(*max_deviation_mads as u64);
+                deviations
+                    .iter()
+                    .map(|deviation| deviation <= &max_deviation)
+                    .collect()
+            }
+        };
+        Some(keep)
+    }
+}
+
+fn abs_diff(a: &U256, b: &U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn sorted(values: &[U256]) -> Vec<&U256> {
+    let mut sorted: Vec<&U256> = values.iter().collect();
+    sorted.sort_unstable();
+    sorted
+}
+
+pub fn lower_median(values: &[U256]) -> Option<U256> {
+    let index = values.len().checked_sub(1)? / 2;
+    Some(sorted(values)[index].clone())
+}
+
+pub fn upper_median(values: &[U256]) -> Option<U256> {
+    sorted(values)
+        .get(values.len() / 2)
+        .map(|&value| value.clone())
+}
+
+/// Mean of the values left after dropping `trim_bps` of them, rounded
+/// down, from each end; `trim_bps` must be below 5000
+pub fn trimmed_mean(values: &[U256], trim_bps: u16) -> Option<U256> {
+    let trim = values.len() * trim_bps as usize / BPS_DENOMINATOR as usize;
+    let sorted = sorted(values);
+    mean(sorted.get(trim..values.len().checked_sub(trim)?)?)
+}
+
+/// Mean rounded down, summing quotients and remainders separately so that
+/// the sum never exceeds the largest value
+fn mean(values: &[&U256]) -> Option<U256> {
+    if values.is_empty() {
+        return None;
+    }
+    let count = values.len() as u64;
+    let mut quotients = U256::ZERO;
+    let mut remainders: u128 = 0;
+    for value in values {
+        let (quotient, remainder) = value.divrem_u64(count)?;
+        quotients += &quotient;
+        remainders += remainder as u128;
+    }
+    Some(quotients + U256::from(remainders / count as u128))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(values: &[u64]) -> Vec<U256> {
+        values.iter().map(|&value| U256::from(value)).collect()
+    }
+
+    #[test]
+    fn test_lower_upper_median() {
+        let even = values(&[4, 1, 3, 2]);
+        assert_eq!(lower_median(&even), Some(U256::from(2)));
+        assert_eq!(upper_median(&even), Some(U256::from(3)));
+
+        let odd = values(&[5, 1, 3]);
+        assert_eq!(lower_median(&odd), Some(U256::from(3)));
+        assert_eq!(upper_median(&odd), Some(U256::from(3)));
+
+        assert_eq!(lower_median(&[]), None);
+        assert_eq!(upper_median(&[]), None);
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let values = values(&[1, 10, 11, 12, 1_000]);
+        // 20% of 5 values drops one from each end
+        assert_eq!(trimmed_mean(&values, 2_000), Some(U256::from(11)));
+        // 10% of 5 values rounds down to nothing dropped
+        assert_eq!(trimmed_mean(&values, 1_000), Some(U256::from(206)));
+        assert_eq!(trimmed_mean(&values, 4_999), Some(U256::from(11)));
+        assert_eq!(trimmed_mean(&[], 2_000), None);
+    }
+
+    /// checks if no overflow occurs
+    #[test]
+    fn test_trimmed_mean_with_max_values() {
+        let values = vec![U256::MAX; 100];
+        assert_eq!(trimmed_mean(&values, 0), Some(U256::MAX));
+
+        let values = vec![U256::MAX, U256::MAX - U256::from(3)];
+        assert_eq!(trimmed_mean(&values, 0), Some(U256::MAX - U256::from(2)));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let values = values(&[7, 3, 5, 9]);
+        let aggregate = |aggregation: Aggregation| {
+            aggregation.aggregate(&values).unwrap().as_u64()
+        };
+        assert_eq!(aggregate(Aggregation::Median), 6);
+        assert_eq!(aggregate(Aggregation::LowerMedian), 5);
+        assert_eq!(aggregate(Aggregation::UpperMedian), 7);
+        assert_eq!(
+            aggregate(Aggregation::TrimmedMean { trim_bps: 2_500 }),
+            6
+        );
+        assert_eq!(aggregate(Aggregation::Min), 3);
+        assert_eq!(aggregate(Aggregation::Max), 9);
+        assert_eq!(Aggregation::Min.aggregate(&[]), None);
+    }
+
+    #[test]
+    fn test_timestamp_aggregation() {
+        let timestamps = [1_003, 1_000, 1_002, 1_001];
+        assert_eq!(
+            TimestampAggregation::Min.aggregate(&timestamps),
+            Some(1_000)
+        );
+        assert_eq!(
+            TimestampAggregation::Median.aggregate(&timestamps),
+            Some(1_001)
+        );
+        assert_eq!(
+            TimestampAggregation::Median.aggregate(&timestamps[..3]),
+            Some(1_002)
+        );
+        assert_eq!(TimestampAggregation::Median.aggregate(&[]), None);
+    }
+
+    #[test]
+    fn test_band_outlier_filter() {
+        let filter = OutlierFilter::Band {
+            max_deviation_bps: 1_000,
+        };
+        let values = values(&[100, 105, 95, 111, 50]);
+        assert_eq!(
+            filter.keep(&values),
+            Some(vec![true, true, true, false, false])
+        );
+        assert_eq!(filter.keep(&[]), None);
+
+        // a zero median only keeps zeros
+        let values = self::values(&[0, 0, 1]);
+        assert_eq!(filter.keep(&values), Some(vec![true, true, false]));
+    }
+
+    #[test]
+    fn test_mad_outlier_filter() {
+        let filter = OutlierFilter::Mad {
+            max_deviation_mads: 3,
+        };
+        // median 102, deviations 2, 0, 4, 2, 898, MAD 2
+        let values = values(&[100, 102, 98, 104, 1_000]);
+        assert_eq!(
+            filter.keep(&values),
+            Some(vec![true, true, true, true, false])
+        );
+    }
+
+    #[test]
+    fn test_mad_outlier_filter_with_zero_mad() {
+        let filter = OutlierFilter::Mad {
+            max_deviation_mads: 3,
+        };
+        let values = values(&[100, 100, 100, 101, 99]);
+        assert_eq!(filter.keep(&values), Some(vec![true; 5]));
+        let values = self::values(&[7, 7, 7, 1_000]);
+        assert_eq!(filter.keep(&values), Some(vec![true; 4]));
+    }
+
+    /// checks if no overflow occurs
+    #[test]
+    fn test_outlier_filter_with_max_values() {
+        let values = vec![U256::ZERO, U256::MAX, U256::MAX >> 1];
+        let mad = OutlierFilter::Mad {
+            max_deviation_mads: 255,
+        };
+        assert_eq!(mad.keep(&values), Some(vec![true, true, true]));
+        let band = OutlierFilter::Band {
+            max_deviation_bps: u64::MAX,
+        };
+        assert_eq!(band.keep(&values), Some(vec![true, true, true]));
+    }
+
+    #[test]
+    fn test_aggregation_is_valid() {
+        assert!(Aggregation::Median.is_valid());
+        assert!(Aggregation::TrimmedMean { trim_bps: 4_999 }.is_valid());
+        assert!(!Aggregation::TrimmedMean { trim_bps: 5_000 }.is_valid());
+        assert!(!OutlierFilter::Mad {
+            max_deviation_mads: 0
+        }
+        .is_valid());
+        assert!(!OutlierFilter::Band {
+            max_deviation_bps: 0
+        }
+        .is_valid());
+    }
+}
@@ -10,6 +10,22 @@ pub const TIMESTAMP_BS: usize = 6;
 pub const REDSTONE_MARKER_BS: usize = 9;
 pub const REDSTONE_MARKER: [u8; 9] = [0, 0, 2, 237, 87, 1, 30, 0, 0]; // 0x000002ed57011e0000
 
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Decimals of RedStone values unless overridden by the feed config
+pub const REDSTONE_DECIMALS: u8 = 8;
+
+pub const MAX_SIGNERS: usize = 32;
+// 4-byte length + 73 bytes per entry within the 1024 bytes of return data
+pub const MAX_GET_PRICES: usize = 13;
+pub const MAX_SNAPSHOT_FEEDS: usize = 16;
+pub const MAX_BASKET_COMPONENTS: usize = 16;
+
+pub const PRICE_DATA_VERSION: u8 = 1;
+pub const CONFIG_ACCOUNT_VERSION: u8 = 1;
+// discriminator + feed id + value + timestamp + write timestamp
+pub const LEGACY_PRICE_DATA_SIZE: usize = 8 + 32 + 32 + 8 + 8;
+
 pub const SIGNERS: [SignerAddress; 10] = [
     [
         0x10, 0x9B, 0x4A, 0x31, 0x8A, 0x4F, 0x5D, 0xDC, 0xBC, 0xA6, 0x34,
@@ -0,0 +1,514 @@
+//! Helpers for programs reading RedStone prices, available to dependents
+//! through the `cpi` feature.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::get_return_data;
+use anchor_lang::Discriminator;
+use zkp_u256::U256;
+
+use crate::constants::PRICE_DATA_VERSION;
+use crate::decimals;
+use crate::error::RedstoneError;
+use crate::instructions::make_price_seed;
+use crate::state::*;
+use crate::util::deviation_bps;
+
+/// Price read from a verified price account
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Price {
+    pub feed_id: FeedId,
+    /// Big-endian value as signed by the RedStone nodes
+    pub value: Value,
+    /// Data package timestamp, ms
+    pub timestamp: u64,
+    /// Block timestamp of the update, ms
+    pub write_timestamp: u64,
+    /// Number of decimals of `value`
+    pub decimals: u8,
+    /// Configured signers backing `value`, see `PriceData::signer_bitmap`
+    pub signer_bitmap: u32,
+    pub signer_count: u8,
+    /// Lowest and highest of the signed values `value` was aggregated from
+    pub lowest_value: Value,
+    pub highest_value: Value,
+    /// Slot of the update
+    pub write_slot: u64,
+    /// Incremented with every update, see `PriceData::round_id`
+    pub round_id: u64,
+}
+
+impl Price {
+    pub fn value_as_u128(&self) -> Result<u128> {
+        if self.value[..16].iter().any(|&b| b != 0) {
+            return Err(RedstoneError::NumberOverflow.into());
+        }
+        Ok(u128::from_be_bytes(self.value[16..].try_into().unwrap()))
+    }
+
+    /// Value rescaled to `decimals`, truncated when scaling down
+    pub fn to_u64(&self, decimals: u8) -> Result<u64> {
+        decimals::to_u64(&self.value(), self.decimals, decimals)
+    }
+
+    /// Value rescaled to `decimals`, truncated when scaling down
+    pub fn to_u128(&self, decimals: u8) -> Result<u128> {
+        decimals::to_u128(&self.value(), self.decimals, decimals)
+    }
+
+    /// Distance between the lowest and the highest signed value, in basis
+    /// points of the lowest one
+    pub fn spread_bps(&self) -> Option<u64> {
+        deviation_bps(
+            &U256::from_bytes_be(&self.lowest_value),
+            &U256::from_bytes_be(&self.highest_value),
+        )
+    }
+
+    pub fn to_decimal_string(&self) -> String {
+        decimals::format_decimal(&self.value(), self.decimals)
+    }
+
+    fn value(&self) -> U256 {
+        U256::from_bytes_be(&self.value)
+    }
+}
+
+pub fn price_account_address(feed_id: &FeedId) -> Pubkey {
+    Pubkey::find_program_address(
+        &[&make_price_seed(), feed_id.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Loads the price of `feed_id`, rejecting it if the package timestamp is
+/// older than `max_age_ms` relative to the current block
+pub fn load_price(
+    account: &AccountInfo,
+    feed_id: &FeedId,
+    max_age_ms: u64,
+) -> Result<Price> {
+    let now_ms = Clock::get()?.unix_timestamp as u64 * 1000;
+    load_price_at(account, feed_id, max_age_ms, now_ms)
+}
+
+pub fn load_price_at(
+    account: &AccountInfo,
+    feed_id: &FeedId,
+    max_age_ms: u64,
+    now_ms: u64,
+) -> Result<Price> {
+    let price_data = load_price_data(account, feed_id)?;
+    if now_ms.saturating_sub(price_data.timestamp) > max_age_ms {
+        msg!(
+            "Price age: {} - {} > {}",
+            now_ms,
+            price_data.timestamp,
+            max_age_ms
+        );
+        return Err(RedstoneError::StalePrice.into());
+    }
+    Ok(Price {
+        feed_id: price_data.feed_id,
+        value: price_data.value,
+        timestamp: price_data.timestamp,
+        write_timestamp: price_data.write_timestamp,
+        decimals: price_data.decimals,
+        signer_bitmap: price_data.signer_bitmap,
+        signer_count: price_data.signer_count,
+        lowest_value: price_data.lowest_value,
+        highest_value: price_data.highest_value,
+        write_slot: price_data.write_slot,
+        round_id: price_data.round_id,
+    })
+}
+
+pub fn snapshot_address(group_id: &FeedId) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"snapshot", group_id.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Loads the snapshot of `group_id`, rejecting it if the package timestamp
+/// is older than `max_age_ms` relative to the current block
+pub fn load_snapshot(
+    account: &AccountInfo,
+    group_id: &FeedId,
+    max_age_ms: u64,
+) -> Result<PriceSnapshot> {
+    let now_ms = Clock::get()?.unix_timestamp as u64 * 1000;
+    load_snapshot_at(account, group_id, max_age_ms, now_ms)
+}
+
+pub fn load_snapshot_at(
+    account: &AccountInfo,
+    group_id: &FeedId,
+    max_age_ms: u64,
+    now_ms: u64,
+) -> Result<PriceSnapshot> {
+    if account.key != &snapshot_address(group_id) {
+        return Err(RedstoneError::InvalidPriceAccount.into());
+    }
+    if account.owner != &crate::ID {
+        return Err(RedstoneError::InvalidPriceAccountOwner.into());
+    }
+    let snapshot =
+        PriceSnapshot::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    if now_ms.saturating_sub(snapshot.timestamp) > max_age_ms {
+        msg!(
+            "Snapshot age: {} - {} > {}",
+            now_ms,
+            snapshot.timestamp,
+            max_age_ms
+        );
+        return Err(RedstoneError::StalePrice.into());
+    }
+    Ok(snapshot)
+}
+
+/// Loads the price of `feed_id` for `get_prices`, reporting missing,
+/// outdated and stale price accounts in the status instead of failing
+pub fn load_price_entry(
+    account: &AccountInfo,
+    feed_id: &FeedId,
+    max_age_ms: Option<u64>,
+    now_ms: u64,
+) -> Result<PriceEntry> {
+    if account.key != &price_account_address(feed_id) {
+        return Err(RedstoneError::InvalidPriceAccount.into());
+    }
+    let mut entry = PriceEntry {
+        feed_id: *feed_id,
+        value: Value::default(),
+        timestamp: 0,
+        status: PriceStatus::Missing,
+    };
+    if account.owner != &crate::ID {
+        return Ok(entry);
+    }
+    let price_data = match load_price_data(account, feed_id) {
+        Ok(price_data) => price_data,
+        Err(err) if err == RedstoneError::UnsupportedAccountLayout.into() => {
+            entry.status = PriceStatus::Outdated;
+            return Ok(entry);
+        }
+        Err(err) => return Err(err),
+    };
+    entry.value = price_data.value;
+    entry.timestamp = price_data.timestamp;
+    entry.status = match max_age_ms {
+        Some(max_age_ms)
+            if now_ms.saturating_sub(price_data.timestamp) > max_age_ms =>
+        {
+            PriceStatus::Stale
+        }
+        _ => PriceStatus::Ok,
+    };
+    Ok(entry)
+}
+
+/// Loads the price of `feed_id` as Chainlink round data, without any
+/// staleness checks, like `latestRoundData`
+pub fn load_round_data(
+    account: &AccountInfo,
+    feed_id: &FeedId,
+) -> Result<RoundData> {
+    Ok(RoundData::from(&load_price_data(account, feed_id)?))
+}
+
+/// Reads the round data returned by a `latest_round_data` CPI
+pub fn round_data_from_return_data() -> Result<RoundData> {
+    let (program_id, data) =
+        get_return_data().ok_or(RedstoneError::InvalidReturnData)?;
+    if program_id != crate::ID {
+        return Err(RedstoneError::InvalidReturnData.into());
+    }
+    RoundData::try_from_slice(&data)
+        .map_err(|_| RedstoneError::InvalidReturnData.into())
+}
+
+/// Verifies the account and returns a copy of its data, without any
+/// staleness checks
+pub fn load_price_data(
+    account: &AccountInfo,
+    feed_id: &FeedId,
+) -> Result<PriceData> {
+    if account.key != &price_account_address(feed_id) {
+        return Err(RedstoneError::InvalidPriceAccount.into());
+    }
+    if account.owner != &crate::ID {
+        return Err(RedstoneError::InvalidPriceAccountOwner.into());
+    }
+
+    let data = account.try_borrow_data()?;
+    let size = std::mem::size_of::<PriceData>();
+    if data.len() < 8 + size || data[..8] != PriceData::DISCRIMINATOR {
+        return Err(RedstoneError::UnsupportedAccountLayout.into());
+    }
+    let price_data: PriceData =
+        bytemuck::pod_read_unaligned(&data[8..8 + size]);
+    if price_data.version != PRICE_DATA_VERSION {
+        return Err(RedstoneError::UnsupportedAccountLayout.into());
+    }
+    if &price_data.feed_id != feed_id {
+        return Err(RedstoneError::FeedIdMismatch.into());
+    }
+
+    Ok(price_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn feed_id(name: &str) -> FeedId {
+        name.parse().unwrap()
+    }
+
+    fn price_account_data(price_data: &PriceData) -> Vec<u8> {
+        let mut data = PriceData::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(price_data));
+        data
+    }
+
+    fn with_account<R>(
+        key: Pubkey,
+        owner: Pubkey,
+        mut data: Vec<u8>,
+        f: impl FnOnce(&AccountInfo) -> R,
+    ) -> R {
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        f(&account)
+    }
+
+    fn btc_price_data() -> PriceData {
+        let mut price_data = PriceData::zeroed();
+        price_data.feed_id = feed_id("BTC");
+        price_data.value[31] = 42;
+        price_data.timestamp = 1_000_000;
+        price_data.write_timestamp = 1_001_000;
+        price_data.version = PRICE_DATA_VERSION;
+        price_data.decimals = 2;
+        price_data.signer_bitmap = 0b1011;
+        price_data.signer_count = 3;
+        price_data.lowest_value[31] = 40;
+        price_data.highest_value[31] = 44;
+        price_data.write_slot = 250;
+        price_data.round_id = 7;
+        price_data
+    }
+
+    #[test]
+    fn test_load_price() {
+        let btc = feed_id("BTC");
+        let data = price_account_data(&btc_price_data());
+        let price = with_account(
+            price_account_address(&btc),
+            crate::ID,
+            data,
+            |account| load_price_at(account, &btc, 60_000, 1_060_000),
+        )
+        .unwrap();
+        assert_eq!(price.feed_id, btc);
+        assert_eq!(price.value_as_u128().unwrap(), 42);
+        assert_eq!(price.to_u64(4).unwrap(), 4_200);
+        assert_eq!(price.to_u64(1).unwrap(), 4);
+        assert_eq!(price.to_decimal_string(), "0.42");
+        assert_eq!(price.timestamp, 1_000_000);
+        assert_eq!(price.signer_bitmap, 0b1011);
+        assert_eq!(price.signer_count, 3);
+        assert_eq!(price.spread_bps(), Some(1_000));
+        assert_eq!(price.write_slot, 250);
+        assert_eq!(price.round_id, 7);
+    }
+
+    #[test]
+    fn test_load_round_data() {
+        let btc = feed_id("BTC");
+        let price_data = btc_price_data();
+        let round_data = with_account(
+            price_account_address(&btc),
+            crate::ID,
+            price_account_data(&price_data),
+            |account| load_round_data(account, &btc),
+        )
+        .unwrap();
+        assert_eq!(
+            round_data,
+            RoundData {
+                round_id: 7,
+                answer: price_data.value,
+                started_at: 1_000_000,
+                updated_at: 1_001_000,
+                answered_in_round: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_price_entry() {
+        let btc = feed_id("BTC");
+        let address = price_account_address(&btc);
+        let data = price_account_data(&btc_price_data());
+        let load = |owner, data: Vec<u8>, max_age_ms| {
+            with_account(address, owner, data, |account| {
+                load_price_entry(account, &btc, max_age_ms, 1_060_001)
+            })
+        };
+
+        let entry = load(crate::ID, data.clone(), None).unwrap();
+        assert_eq!(entry.status, PriceStatus::Ok);
+        assert_eq!(entry.value, btc_price_data().value);
+        assert_eq!(entry.timestamp, 1_000_000);
+        assert_eq!(
+            load(crate::ID, data.clone(), Some(60_000)).unwrap().status,
+            PriceStatus::Stale
+        );
+        assert_eq!(
+            load(crate::ID, data[..88].to_vec(), None).unwrap().status,
+            PriceStatus::Outdated
+        );
+
+        let missing = load(Pubkey::default(), vec![], None).unwrap();
+        assert_eq!(missing.status, PriceStatus::Missing);
+        assert_eq!(missing.feed_id, btc);
+        assert_eq!(missing.value, Value::default());
+
+        let res = with_account(
+            price_account_address(&feed_id("ETH")),
+            crate::ID,
+            data,
+            |account| load_price_entry(account, &btc, None, 1_000_000),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            RedstoneError::InvalidPriceAccount.into()
+        );
+    }
+
+    #[test]
+    fn test_load_snapshot() {
+        let majors = feed_id("MAJORS");
+        let snapshot = PriceSnapshot {
+            group_id: majors,
+            timestamp: 1_000_000,
+            write_timestamp: 1_001_000,
+            write_slot: 250,
+            round_id: 2,
+            entries: vec![SnapshotEntry {
+                feed_id: feed_id("BTC"),
+                value: btc_price_data().value,
+                decimals: 8,
+                signer_count: 3,
+            }],
+        };
+        let mut data = Vec::new();
+        snapshot.try_serialize(&mut data).unwrap();
+        let load = |data: Vec<u8>, now_ms| {
+            with_account(
+                snapshot_address(&majors),
+                crate::ID,
+                data,
+                |account| load_snapshot_at(account, &majors, 60_000, now_ms),
+            )
+        };
+
+        let loaded = load(data.clone(), 1_060_000).unwrap();
+        assert_eq!(loaded.round_id, 2);
+        assert_eq!(
+            loaded.entry(&feed_id("BTC")).unwrap().value,
+            btc_price_data().value
+        );
+        assert!(loaded.entry(&feed_id("ETH")).is_none());
+        assert_eq!(
+            load(data.clone(), 1_060_001).unwrap_err(),
+            RedstoneError::StalePrice.into()
+        );
+
+        let res = with_account(
+            snapshot_address(&feed_id("OTHER")),
+            crate::ID,
+            data,
+            |account| load_snapshot_at(account, &majors, 60_000, 1_000_000),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            RedstoneError::InvalidPriceAccount.into()
+        );
+    }
+
+    #[test]
+    fn test_load_price_rejects_stale() {
+        let btc = feed_id("BTC");
+        let data = price_account_data(&btc_price_data());
+        let res = with_account(
+            price_account_address(&btc),
+            crate::ID,
+            data,
+            |account| load_price_at(account, &btc, 60_000, 1_060_001),
+        );
+        assert_eq!(res.unwrap_err(), RedstoneError::StalePrice.into());
+    }
+
+    #[test]
+    fn test_load_price_rejects_invalid_account() {
+        let btc = feed_id("BTC");
+        let eth = feed_id("ETH");
+        let data = price_account_data(&btc_price_data());
+
+        let res = with_account(
+            price_account_address(&eth),
+            crate::ID,
+            data.clone(),
+            |account| load_price_at(account, &btc, 60_000, 1_000_000),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            RedstoneError::InvalidPriceAccount.into()
+        );
+
+        let res = with_account(
+            price_account_address(&btc),
+            Pubkey::default(),
+            data.clone(),
+            |account| load_price_at(account, &btc, 60_000, 1_000_000),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            RedstoneError::InvalidPriceAccountOwner.into()
+        );
+
+        let mut eth_data = btc_price_data();
+        eth_data.feed_id = eth;
+        let res = with_account(
+            price_account_address(&btc),
+            crate::ID,
+            price_account_data(&eth_data),
+            |account| load_price_at(account, &btc, 60_000, 1_000_000),
+        );
+        assert_eq!(res.unwrap_err(), RedstoneError::FeedIdMismatch.into());
+
+        let res = with_account(
+            price_account_address(&btc),
+            crate::ID,
+            data[..88].to_vec(),
+            |account| load_price_at(account, &btc, 60_000, 1_000_000),
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            RedstoneError::UnsupportedAccountLayout.into()
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Fixed-point conversions of RedStone values
+use anchor_lang::prelude::*;
+use zkp_u256::U256;
+
+use crate::error::RedstoneError;
+use crate::util::u256_to_decimal_string;
+
+/// 10^77 is the largest power of ten below 2^256
+pub const MAX_POW10: u8 = 77;
+
+pub fn pow10(exponent: u8) -> Option<U256> {
+    if exponent > MAX_POW10 {
+        return None;
+    }
+    U256::from(10u64).pow(exponent as u64)
+}
+
+/// Multiplies `value` by 10^`decimals`, failing on overflow
+pub fn scale_up(value: &U256, decimals: u8) -> Result<U256> {
+    if value.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    let factor = pow10(decimals).ok_or(RedstoneError::NumberOverflow)?;
+    if *value > U256::MAX / &factor {
+        return Err(RedstoneError::NumberOverflow.into());
+    }
+    Ok(value * &factor)
+}
+
+/// Divides `value` by 10^`decimals`, truncating the remainder
+pub fn scale_down(value: &U256, decimals: u8) -> U256 {
+    match pow10(decimals) {
+        Some(factor) => value / &factor,
+        None => U256::ZERO,
+    }
+}
+
+pub fn rescale(
+    value: &U256,
+    from_decimals: u8,
+    to_decimals: u8,
+) -> Result<U256> {
+    if to_decimals >= from_decimals {
+        scale_up(value, to_decimals - from_decimals)
+    } else {
+        Ok(scale_down(value, from_decimals - to_decimals))
+    }
+}
+
+/// `numerator / denominator` with `decimals` decimals, truncated, the
+/// operands having their own decimals
+pub fn ratio(
+    numerator: &U256,
+    numerator_decimals: u8,
+    denominator: &U256,
+    denominator_decimals: u8,
+    decimals: u8,
+) -> Result<U256> {
+    if denominator.is_zero() {
+        return Err(RedstoneError::DivisionByZero.into());
+    }
+    let exponent = decimals as i16 + denominator_decimals as i16
+        - numerator_decimals as i16;
+    let (numerator, denominator) = if exponent >= 0 {
+        let exponent = u8::try_from(exponent)
+            .map_err(|_| RedstoneError::NumberOverflow)?;
+        (scale_up(numerator, exponent)?, denominator.clone())
+    } else {
+        let exponent = u8::try_from(-exponent)
+            .map_err(|_| RedstoneError::NumberOverflow)?;
+        (numerator.clone(), scale_up(denominator, exponent)?)
+    };
+    Ok(numerator / &denominator)
+}
+
+/// Sum of the `(value, value decimals, weight)` components with
+/// `decimals` decimals, the weights having `weight_decimals`. Every
+/// component is truncated on its own, failing on overflow
+pub fn weighted_sum(
+    components: &[(U256, u8, u64)],
+    weight_decimals: u8,
+    decimals: u8,
+) -> Result<U256> {
+    let mut sum = U256::ZERO;
+    for (value, value_decimals, weight) in components {
+        let weight = U256::from(*weight);
+        if !weight.is_zero() && *value > U256::MAX / &weight {
+            return Err(RedstoneError::NumberOverflow.into());
+        }
+        let product_decimals = value_decimals
+            .checked_add(weight_decimals)
+            .ok_or(RedstoneError::NumberOverflow)?;
+        let term = rescale(&(value * &weight), product_decimals, decimals)?;
+        if sum > U256::MAX - &term {
+            return Err(RedstoneError::NumberOverflow.into());
+        }
+        sum += &term;
+    }
+    Ok(sum)
+}
+
+pub fn to_u64(
+    value: &U256,
+    from_decimals: u8,
+    to_decimals: u8,
+) -> Result<u64> {
+    let value = rescale(value, from_decimals, to_decimals)?;
+    if value.bits() > 64 {
+        return Err(RedstoneError::NumberOverflow.into());
+    }
+    Ok(value.as_u64())
+}
+
+pub fn to_u128(
+    value: &U256,
+    from_decimals: u8,
+    to_decimals: u8,
+) -> Result<u128> {
+    let value = rescale(value, from_decimals, to_decimals)?;
+    if value.bits() > 128 {
+        return Err(RedstoneError::NumberOverflow.into());
+    }
+    Ok(value.as_u128())
+}
+
+/// Formats `value` with the decimal point, e.g. `"1234.56789000"`
+pub fn format_decimal(value: &U256, decimals: u8) -> String {
+    let digits = u256_to_decimal_string(value);
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    format!("{}.{}", integer, fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescale() {
+        let value = U256::from(123_456_789u64);
+        assert_eq!(rescale(&value, 8, 8).unwrap(), value);
+        assert_eq!(
+            rescale(&value, 8, 10).unwrap(),
+            U256::from(12_345_678_900u64)
+        );
+        assert_eq!(rescale(&value, 8, 6).unwrap(), U256::from(1_234_567u64));
+        assert_eq!(rescale(&value, 80, 0).unwrap(), U256::ZERO);
+        assert_eq!(rescale(&U256::ZERO, 0, 80).unwrap(), U256::ZERO);
+    }
+
+    /// checks if no overflow occurs
+    #[test]
+    fn test_scale_up_with_max_values() {
+        assert!(scale_up(&U256::MAX, 1).is_err());
+        assert!(scale_up(&U256::ONE, 78).is_err());
+        assert_eq!(scale_up(&U256::ONE, 77).unwrap(), pow10(77).unwrap());
+        assert_eq!(scale_down(&U256::MAX, 77), U256::ONE);
+    }
+
+    #[test]
+    fn test_ratio() {
+        // ETH/BTC from 8-decimal USD prices
+        let eth = U256::from(250_000_000_000u64);
+        let btc = U256::from(6_000_000_000_000u64);
+        assert_eq!(
+            ratio(&eth, 8, &btc, 8, 8).unwrap(),
+            U256::from(4_166_666u64)
+        );
+        // SOL/USDC with a 6-decimal denominator
+        let sol = U256::from(15_000_000_000u64);
+        let usdc = U256::from(1_000_100u64);
+        assert_eq!(
+            ratio(&sol, 8, &usdc, 6, 2).unwrap(),
+            U256::from(14_998u64)
+        );
+        assert_eq!(ratio(&btc, 18, &eth, 0, 8).unwrap(), U256::from(0u64));
+        assert!(ratio(&eth, 8, &U256::ZERO, 8, 8).is_err());
+    }
+
+    /// checks if no overflow occurs
+    #[test]
+    fn test_ratio_with_max_values() {
+        assert!(ratio(&U256::MAX, 0, &U256::ONE, 0, 1).is_err());
+        assert_eq!(
+            ratio(&U256::MAX, 0, &U256::MAX, 0, 0).unwrap(),
+            U256::ONE
+        );
+        assert!(ratio(&U256::ONE, 255, &U256::ONE, 0, 0).is_err());
+        // an exponent of 256 does not wrap around to 1 / 1
+        assert!(ratio(&U256::ONE, 0, &U256::ONE, 255, 1).is_err());
+    }
+
+    #[test]
+    fn test_weighted_sum() {
+        // 0.5 ETH at 2500 and 1000 USDC at 1.0001
+        let components = [
+            (U256::from(250_000_000_000u64), 8, 500_000),
+            (U256::from(1_000_100u64), 6, 1_000_000_000),
+        ];
+        assert_eq!(
+            weighted_sum(&components, 6, 8).unwrap(),
+            U256::from(225_010_000_000u64)
+        );
+        assert_eq!(
+            weighted_sum(&components, 6, 0).unwrap(),
+            U256::from(2_250u64)
+        );
+        assert_eq!(weighted_sum(&[], 6, 8).unwrap(), U256::ZERO);
+    }
+
+    /// checks if no overflow occurs
+    #[test]
+    fn test_weighted_sum_with_max_values() {
+        let max = [(U256::MAX, 0, 1)];
+        assert_eq!(weighted_sum(&max, 0, 0).unwrap(), U256::MAX);
+        assert!(weighted_sum(&[(U256::MAX, 0, 2)], 0, 0).is_err());
+        assert!(weighted_sum(&[(U256::MAX, 0, 1), (U256::ONE, 0, 1)], 0, 0)
+            .is_err());
+        assert!(weighted_sum(&[(U256::ONE, 200, 1)], 100, 0).is_err());
+        assert_eq!(
+            weighted_sum(&[(U256::MAX, 0, 0)], 0, 0).unwrap(),
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn test_to_uint() {
+        let value = U256::from(u64::MAX);
+        assert_eq!(to_u64(&value, 8, 8).unwrap(), u64::MAX);
+        assert!(to_u64(&value, 8, 9).is_err());
+        assert_eq!(to_u128(&value, 8, 9).unwrap(), u64::MAX as u128 * 10);
+        assert_eq!(to_u64(&value, 8, 0).unwrap(), u64::MAX / 100_000_000);
+        assert!(to_u128(&U256::MAX, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        let value = U256::from(123_456_789u64);
+        assert_eq!(format_decimal(&value, 8), "1.23456789");
+        assert_eq!(format_decimal(&value, 0), "123456789");
+        assert_eq!(format_decimal(&value, 10), "0.0123456789");
+        assert_eq!(format_decimal(&U256::ZERO, 2), "0.00");
+        assert_eq!(
+            format_decimal(&U256::MAX, 8),
+            "1157920892373161954235709850086879078532699846656405640394575840079131.29639935"
+        );
+    }
+}
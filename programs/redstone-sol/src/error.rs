@@ -40,4 +40,73 @@ pub enum RedstoneError {
 
     #[msg("Packages need to have the same timestamp")]
     TimestampMismatch,
+
+    #[msg("No pending price to accept")]
+    NoPendingPrice,
+
+    #[msg("Value is outside of the feed bounds")]
+    ValueOutOfBounds,
+
+    #[msg("Invalid feed config")]
+    InvalidFeedConfig,
+
+    #[msg("Feed is not registered or inactive")]
+    FeedNotRegistered,
+
+    #[msg("Feed is already registered")]
+    FeedAlreadyRegistered,
+
+    #[msg("Feed is still registered as active")]
+    FeedStillActive,
+
+    #[msg("Account is already migrated")]
+    AccountAlreadyMigrated,
+
+    #[msg("Unsupported account layout")]
+    UnsupportedAccountLayout,
+
+    #[msg("Too many signers")]
+    TooManySigners,
+
+    #[msg("Price account address does not match the feed")]
+    InvalidPriceAccount,
+
+    #[msg("Price account is not owned by the program")]
+    InvalidPriceAccountOwner,
+
+    #[msg("Price account holds a different feed")]
+    FeedIdMismatch,
+
+    #[msg("Price is stale")]
+    StalePrice,
+
+    #[msg("Invalid feed id")]
+    InvalidFeedId,
+
+    #[msg("Update too frequent")]
+    UpdateTooFrequent,
+
+    #[msg("Invalid return data")]
+    InvalidReturnData,
+
+    #[msg("Too many feeds")]
+    TooManyFeeds,
+
+    #[msg("Number of accounts does not match")]
+    AccountCountMismatch,
+
+    #[msg("Invalid feed group")]
+    InvalidFeedGroup,
+
+    #[msg("Invalid derived feed")]
+    InvalidDerivedFeed,
+
+    #[msg("Division by zero")]
+    DivisionByZero,
+
+    #[msg("Invalid basket feed")]
+    InvalidBasketFeed,
+
+    #[msg("Feed id is used by a derived or basket feed")]
+    FeedIdInUse,
 }
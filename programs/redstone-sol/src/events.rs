@@ -0,0 +1,15 @@
+//! Events of the program. They are logged even by failing instructions,
+//! so rejected updates can be monitored through the transaction logs
+use anchor_lang::prelude::*;
+
+use crate::state::{FeedId, Value};
+
+/// Aggregated value rejected by the bounds of its feed config
+#[event]
+pub struct PriceOutOfBounds {
+    pub feed_id: FeedId,
+    /// Big-endian value
+    pub value: Value,
+    pub min_value: Option<Value>,
+    pub max_value: Option<Value>,
+}
@@ -0,0 +1,141 @@
+//! RedStone feed identifiers.
+//!
+//! Names of up to 32 bytes are stored as ASCII, right-padded with zeros.
+//! Longer names are stored as their keccak256 hash.
+use std::fmt;
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::error::RedstoneError;
+use crate::redstone::keccak256;
+
+pub const FEED_ID_BS: usize = 32;
+/// Upper bound for the names hashed into feed ids
+pub const MAX_FEED_NAME_LEN: usize = 128;
+
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Pod,
+    Zeroable,
+)]
+#[repr(transparent)]
+pub struct FeedId(pub [u8; FEED_ID_BS]);
+
+impl FeedId {
+    pub fn as_bytes(&self) -> &[u8; FEED_ID_BS] {
+        &self.0
+    }
+
+    /// Name of an ASCII-encoded id, `None` for hashed or malformed ids
+    pub fn name(&self) -> Option<&str> {
+        let len = self.0.iter().position(|&b| b == 0).unwrap_or(FEED_ID_BS);
+        let (name, padding) = self.0.split_at(len);
+        if !is_valid_name(name) || padding.iter().any(|&b| b != 0) {
+            return None;
+        }
+        std::str::from_utf8(name).ok()
+    }
+}
+
+fn is_valid_name(name: &[u8]) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_FEED_NAME_LEN
+        && name.iter().all(|b| b.is_ascii_graphic())
+}
+
+impl FromStr for FeedId {
+    type Err = RedstoneError;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        if !is_valid_name(name.as_bytes()) {
+            return Err(RedstoneError::InvalidFeedId);
+        }
+        if name.len() > FEED_ID_BS {
+            return Ok(FeedId(keccak256(name.as_bytes())));
+        }
+        let mut feed_id = [0u8; FEED_ID_BS];
+        feed_id[..name.len()].copy_from_slice(name.as_bytes());
+        Ok(FeedId(feed_id))
+    }
+}
+
+impl From<[u8; FEED_ID_BS]> for FeedId {
+    fn from(bytes: [u8; FEED_ID_BS]) -> Self {
+        FeedId(bytes)
+    }
+}
+
+impl AsRef<[u8]> for FeedId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Prints the name, hashed ids are printed as hex
+impl fmt::Display for FeedId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => {
+                f.write_str("0x")?;
+                self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for FeedId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FeedId({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_id_from_str() {
+        let feed_id: FeedId = "BTC".parse().unwrap();
+        assert_eq!(&feed_id.0[..3], b"BTC");
+        assert!(feed_id.0[3..].iter().all(|&b| b == 0));
+        assert_eq!(feed_id.to_string(), "BTC");
+
+        let name = "A".repeat(FEED_ID_BS);
+        assert_eq!(name.parse::<FeedId>().unwrap().to_string(), name);
+    }
+
+    #[test]
+    fn test_feed_id_hashed() {
+        let name = "LONG_FEED_NAME_EXCEEDING_THIRTY_TWO_BYTES";
+        let feed_id: FeedId = name.parse().unwrap();
+        assert_eq!(feed_id.0, keccak256(name.as_bytes()));
+        assert_eq!(feed_id.name(), None);
+        assert!(feed_id.to_string().starts_with("0x"));
+        assert_eq!(feed_id.to_string().len(), 2 + 2 * FEED_ID_BS);
+    }
+
+    #[test]
+    fn test_feed_id_validation() {
+        assert!("".parse::<FeedId>().is_err());
+        assert!("BTC USD".parse::<FeedId>().is_err());
+        assert!("BTC\0".parse::<FeedId>().is_err());
+        assert!("ĄĘ".parse::<FeedId>().is_err());
+        assert!("A".repeat(MAX_FEED_NAME_LEN + 1).parse::<FeedId>().is_err());
+        assert!("USDC.e".parse::<FeedId>().is_ok());
+
+        let mut feed_id: FeedId = "BTC".parse().unwrap();
+        feed_id.0[5] = b'X';
+        assert_eq!(feed_id.name(), None);
+        assert_eq!(FeedId::default().name(), None);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::error::RedstoneError;
+use crate::state::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+
+use super::make_price_seed;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct AcceptPendingPrice<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        mut,
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump
+    )]
+    pub price_account: AccountLoader<'info, PriceData>,
+}
+
+pub fn accept_pending_price(
+    ctx: Context<AcceptPendingPrice>,
+    feed_id: FeedId,
+) -> Result<()> {
+    let mut price_account = ctx.accounts.price_account.load_mut()?;
+    if price_account.pending_timestamp == 0 {
+        return Err(RedstoneError::NoPendingPrice.into());
+    }
+    // a regular update may have landed after the held one
+    if price_account.timestamp >= price_account.pending_timestamp {
+        return Err(RedstoneError::TimestampTooOld.into());
+    }
+
+    price_account.value = price_account.pending_value;
+    price_account.timestamp = price_account.pending_timestamp;
+    let clock = Clock::get()?;
+    price_account.write_timestamp = clock.unix_timestamp as u64 * 1000;
+    price_account.write_slot = clock.slot;
+    price_account.round_id += 1;
+    price_account.pending_value = Value::default();
+    price_account.pending_timestamp = 0;
+    // signer statistics of the held update are not kept
+    price_account.signer_bitmap = 0;
+    price_account.signer_count = 0;
+    price_account.lowest_value = Value::default();
+    price_account.highest_value = Value::default();
+
+    msg!(
+        "{} {}: {} (accepted)",
+        price_account.timestamp,
+        feed_id,
+        u256_to_num_string(&price_account.value)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use super::make_price_seed;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct ClosePriceAccount<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        seeds = [b"registry"],
+        bump,
+        constraint = !feed_registry.is_active(&feed_id)
+            @ RedstoneError::FeedStillActive
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+    /// CHECK: not deserialized so that accounts with an outdated layout
+    /// can be closed as well
+    #[account(
+        mut,
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump,
+        owner = crate::ID
+    )]
+    pub price_account: UncheckedAccount<'info>,
+    /// CHECK: derived and basket feeds keep writing their price account,
+    /// so their ids cannot be closed
+    #[account(
+        seeds = [b"derived_feed".as_ref(), feed_id.as_ref()],
+        bump,
+        constraint = derived_feed.data_is_empty() @ RedstoneError::FeedIdInUse
+    )]
+    pub derived_feed: UncheckedAccount<'info>,
+    /// CHECK: see `derived_feed`
+    #[account(
+        seeds = [b"basket_feed".as_ref(), feed_id.as_ref()],
+        bump,
+        constraint = basket_feed.data_is_empty() @ RedstoneError::FeedIdInUse
+    )]
+    pub basket_feed: UncheckedAccount<'info>,
+    /// CHECK: only receives the reclaimed lamports
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+pub fn close_price_account(
+    ctx: Context<ClosePriceAccount>,
+    feed_id: FeedId,
+) -> Result<()> {
+    let price_account = ctx.accounts.price_account.to_account_info();
+    let destination = ctx.accounts.destination.to_account_info();
+
+    price_account.try_borrow_mut_data()?.fill(0);
+
+    let lamports = price_account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(RedstoneError::NumberOverflow)?;
+    **price_account.try_borrow_mut_lamports()? = 0;
+
+    price_account.assign(&system_program::ID);
+    price_account.realloc(0, false)?;
+
+    msg!(
+        "Closed price account for {}, {} lamports refunded to {}",
+        feed_id,
+        lamports,
+        destination.key()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use crate::constants::PRICE_DATA_VERSION;
+use crate::decimals::MAX_POW10;
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+use super::make_price_seed;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct CreateDerivedFeed<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        seeds = [b"registry"],
+        bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+    #[account(
+        init,
+        payer = owner,
+        space = DerivedFeed::space(),
+        seeds = [b"derived_feed".as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub derived_feed: Account<'info, DerivedFeed>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<PriceData>(),
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump
+    )]
+    pub price_account: AccountLoader<'info, PriceData>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates `feed_id` as `numerator / denominator` with `decimals`
+/// decimals. Its price account is written on the next update of a source
+/// passing the derived feed accounts
+pub fn create_derived_feed(
+    ctx: Context<CreateDerivedFeed>,
+    feed_id: FeedId,
+    numerator: FeedId,
+    denominator: FeedId,
+    decimals: u8,
+) -> Result<()> {
+    let feed_registry = &ctx.accounts.feed_registry;
+    // derived prices are never written from payloads
+    if numerator == denominator || feed_registry.contains(&feed_id) {
+        return Err(RedstoneError::InvalidDerivedFeed.into());
+    }
+    if !feed_registry.contains(&numerator)
+        || !feed_registry.contains(&denominator)
+    {
+        return Err(RedstoneError::FeedNotRegistered.into());
+    }
+    if decimals > MAX_POW10 {
+        return Err(RedstoneError::InvalidDerivedFeed.into());
+    }
+
+    let derived_feed = &mut ctx.accounts.derived_feed;
+    derived_feed.feed_id = feed_id;
+    derived_feed.numerator = numerator;
+    derived_feed.denominator = denominator;
+    derived_feed.decimals = decimals;
+
+    let mut price_account = ctx.accounts.price_account.load_init()?;
+    price_account.feed_id = feed_id;
+    price_account.version = PRICE_DATA_VERSION;
+    price_account.decimals = decimals;
+    msg!("Created {} = {} / {}", feed_id, numerator, denominator);
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use crate::constants::MAX_SNAPSHOT_FEEDS;
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(group_id: FeedId, feed_ids: Vec<FeedId>)]
+pub struct CreateSnapshot<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        seeds = [b"registry"],
+        bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+    #[account(
+        init,
+        payer = owner,
+        space = PriceSnapshot::space(feed_ids.len()),
+        seeds = [b"snapshot".as_ref(), group_id.as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, PriceSnapshot>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the snapshot of a group of registered feeds
+pub fn create_snapshot(
+    ctx: Context<CreateSnapshot>,
+    group_id: FeedId,
+    feed_ids: Vec<FeedId>,
+) -> Result<()> {
+    if feed_ids.is_empty() || feed_ids.len() > MAX_SNAPSHOT_FEEDS {
+        return Err(RedstoneError::InvalidFeedGroup.into());
+    }
+    for (i, feed_id) in feed_ids.iter().enumerate() {
+        if feed_ids[..i].contains(feed_id) {
+            return Err(RedstoneError::InvalidFeedGroup.into());
+        }
+        if !ctx.accounts.feed_registry.contains(feed_id) {
+            return Err(RedstoneError::FeedNotRegistered.into());
+        }
+    }
+
+    let snapshot = &mut ctx.accounts.snapshot;
+    snapshot.group_id = group_id;
+    snapshot.entries = feed_ids
+        .into_iter()
+        .map(|feed_id| SnapshotEntry {
+            feed_id,
+            value: Value::default(),
+            decimals: 0,
+            signer_count: 0,
+        })
+        .collect();
+    msg!(
+        "Created snapshot {} of {} feeds",
+        group_id,
+        snapshot.entries.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::constants::MAX_GET_PRICES;
+use crate::consumer::load_price_entry;
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Price accounts of the requested feeds are passed in the remaining
+/// accounts, in the same order
+#[derive(Accounts)]
+pub struct GetPrices {}
+
+/// CPI accounts of `GetPrices`, which Anchor generates without the
+/// lifetime its CPI helpers expect, see `__cpi_client_accounts_get_prices`
+/// in the crate root. The price accounts go in the remaining accounts of
+/// the `CpiContext`
+#[cfg(feature = "cpi")]
+#[derive(Default)]
+pub struct GetPricesCpiAccounts<'info> {
+    phantom: std::marker::PhantomData<&'info ()>,
+}
+
+#[cfg(feature = "cpi")]
+impl ToAccountMetas for GetPricesCpiAccounts<'_> {
+    fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![]
+    }
+}
+
+#[cfg(feature = "cpi")]
+impl<'info> ToAccountInfos<'info> for GetPricesCpiAccounts<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![]
+    }
+}
+
+/// Returns the prices of `feed_ids` through the return data, marking the
+/// ones older than `max_age_ms` as stale
+pub fn get_prices<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetPrices>,
+    feed_ids: Vec<FeedId>,
+    max_age_ms: Option<u64>,
+) -> Result<Vec<PriceEntry>> {
+    if feed_ids.len() > MAX_GET_PRICES {
+        return Err(RedstoneError::TooManyFeeds.into());
+    }
+    if feed_ids.len() != ctx.remaining_accounts.len() {
+        return Err(RedstoneError::AccountCountMismatch.into());
+    }
+    let now_ms = Clock::get()?.unix_timestamp as u64 * 1000;
+    feed_ids
+        .iter()
+        .zip(ctx.remaining_accounts)
+        .map(|(feed_id, account)| {
+            load_price_entry(account, feed_id, max_age_ms, now_ms)
+        })
+        .collect()
+}
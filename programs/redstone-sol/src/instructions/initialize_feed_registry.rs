@@ -0,0 +1,23 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializeFeedRegistry<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        init,
+        payer = owner,
+        space = FeedRegistry::space(0),
+        seeds = [b"registry"],
+        bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+    pub system_program: Program<'info, System>,
+}
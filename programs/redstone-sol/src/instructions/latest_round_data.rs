@@ -0,0 +1,26 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+use super::make_price_seed;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct LatestRoundData<'info> {
+    #[account(
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump
+    )]
+    pub price_account: AccountLoader<'info, PriceData>,
+}
+
+/// Returns the stored price as `RoundData` through the return data
+pub fn latest_round_data(
+    ctx: Context<LatestRoundData>,
+    _feed_id: FeedId,
+) -> Result<RoundData> {
+    let price_account = ctx.accounts.price_account.load()?;
+    Ok(RoundData::from(&*price_account))
+}
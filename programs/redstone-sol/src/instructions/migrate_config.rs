@@ -0,0 +1,62 @@
+use crate::constants::CONFIG_ACCOUNT_VERSION;
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Resizes a config created before versioning to its signer list. Such
+/// configs are recognized by their size, they were created with a fixed
+/// amount of excess space, so their version byte is whatever followed the
+/// signer list
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let config_info = ctx.accounts.config_account.to_account_info();
+    let space =
+        ConfigAccount::space(ctx.accounts.config_account.signers.len());
+    if config_info.data_len() == space {
+        return Err(RedstoneError::AccountAlreadyMigrated.into());
+    }
+    msg!(
+        "Migrating config from {} to {} bytes, v{}",
+        config_info.data_len(),
+        space,
+        CONFIG_ACCOUNT_VERSION
+    );
+
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = config_info.lamports();
+    if lamports < rent {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: config_info.clone(),
+                },
+            ),
+            rent - lamports,
+        )?;
+    } else {
+        // the owner paid for the excess space
+        let owner_info = ctx.accounts.owner.to_account_info();
+        **config_info.try_borrow_mut_lamports()? = rent;
+        **owner_info.try_borrow_mut_lamports()? += lamports - rent;
+    }
+    config_info.realloc(space, false)?;
+
+    ctx.accounts.config_account.version = CONFIG_ACCOUNT_VERSION;
+    Ok(())
+}
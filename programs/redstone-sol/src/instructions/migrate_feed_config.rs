@@ -0,0 +1,70 @@
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct MigrateFeedConfig<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    /// CHECK: deserialized by `FeedConfig::load`, which accepts the shorter
+    /// layouts of older versions
+    #[account(
+        mut,
+        seeds = [b"feed_config".as_ref(), feed_id.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub feed_config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a feed config created by an older version to the current size, so
+/// that `set_feed_config` accepts it again
+pub fn migrate_feed_config(
+    ctx: Context<MigrateFeedConfig>,
+    feed_id: FeedId,
+) -> Result<()> {
+    let feed_config_info = ctx.accounts.feed_config.to_account_info();
+    let space = FeedConfig::space();
+    if feed_config_info.data_len() >= space {
+        return Err(RedstoneError::AccountAlreadyMigrated.into());
+    }
+    let feed_config = FeedConfig::load(&feed_config_info)?
+        .ok_or(RedstoneError::UnsupportedAccountLayout)?;
+    msg!(
+        "Migrating feed config of {} from {} to {} bytes",
+        feed_id,
+        feed_config_info.data_len(),
+        space
+    );
+
+    let rent = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(feed_config_info.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: feed_config_info.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    feed_config_info.realloc(space, true)?;
+    feed_config.try_serialize(
+        &mut &mut feed_config_info.try_borrow_mut_data()?[..],
+    )?;
+
+    Ok(())
+}
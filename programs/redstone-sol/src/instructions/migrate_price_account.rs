@@ -0,0 +1,102 @@
+use crate::constants::{
+    LEGACY_PRICE_DATA_SIZE, PRICE_DATA_VERSION, REDSTONE_DECIMALS,
+};
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use bytemuck::Zeroable;
+
+use super::make_price_seed;
+
+/// Layout of the price accounts created before versioning
+#[derive(AnchorDeserialize)]
+struct LegacyPriceData {
+    feed_id: FeedId,
+    value: Value,
+    timestamp: u64,
+    write_timestamp: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct MigratePriceAccount<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    /// CHECK: deserialized by hand, the stored layout may be outdated
+    #[account(
+        mut,
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump,
+        owner = crate::ID
+    )]
+    pub price_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_price_account(
+    ctx: Context<MigratePriceAccount>,
+    _feed_id: FeedId,
+) -> Result<()> {
+    let price_info = ctx.accounts.price_account.to_account_info();
+
+    let space = 8 + std::mem::size_of::<PriceData>();
+    let mut price_data = {
+        let data = price_info.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != PriceData::DISCRIMINATOR {
+            return Err(RedstoneError::UnsupportedAccountLayout.into());
+        }
+        if data.len() == space {
+            return Err(RedstoneError::AccountAlreadyMigrated.into());
+        }
+        if data.len() != LEGACY_PRICE_DATA_SIZE {
+            return Err(RedstoneError::UnsupportedAccountLayout.into());
+        }
+        let legacy = LegacyPriceData::deserialize(&mut &data[8..])?;
+        let mut price_data = PriceData::zeroed();
+        price_data.feed_id = legacy.feed_id;
+        price_data.value = legacy.value;
+        price_data.timestamp = legacy.timestamp;
+        price_data.write_timestamp = legacy.write_timestamp;
+        price_data.decimals = REDSTONE_DECIMALS;
+        // the legacy value counts as the first round
+        if legacy.timestamp != 0 {
+            price_data.round_id = 1;
+        }
+        price_data
+    };
+    msg!("Migrating price account to v{}", PRICE_DATA_VERSION);
+
+    let rent = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(price_info.lamports());
+    if rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: price_info.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+    price_info.realloc(space, true)?;
+
+    price_data.version = PRICE_DATA_VERSION;
+    price_info.try_borrow_mut_data()?[8..space]
+        .copy_from_slice(bytemuck::bytes_of(&price_data));
+
+    Ok(())
+}
@@ -1,3 +1,37 @@
+pub mod accept_pending_price;
+pub mod close_price_account;
+pub mod create_derived_feed;
+pub mod create_snapshot;
+pub mod get_prices;
+pub mod initialize_feed_registry;
+pub mod latest_round_data;
+pub mod migrate_config;
+pub mod migrate_feed_config;
+pub mod migrate_price_account;
 pub mod process_redstone_payload;
+pub mod process_snapshot_payload;
+pub mod register_feed;
+pub mod set_basket_feed;
+pub mod set_feed_active;
+pub mod set_feed_config;
+pub mod set_max_jump_bps;
+pub mod update_basket_feed;
 
+pub use accept_pending_price::*;
+pub use close_price_account::*;
+pub use create_derived_feed::*;
+pub use create_snapshot::*;
+pub use get_prices::*;
+pub use initialize_feed_registry::*;
+pub use latest_round_data::*;
+pub use migrate_config::*;
+pub use migrate_feed_config::*;
+pub use migrate_price_account::*;
 pub use process_redstone_payload::*;
+pub use process_snapshot_payload::*;
+pub use register_feed::*;
+pub use set_basket_feed::*;
+pub use set_feed_active::*;
+pub use set_feed_config::*;
+pub use set_max_jump_bps::*;
+pub use update_basket_feed::*;
@@ -1,3 +1,6 @@
+use crate::constants::{PRICE_DATA_VERSION, REDSTONE_DECIMALS};
+use crate::consumer::{load_price_data, price_account_address};
+use crate::decimals;
 use crate::error::RedstoneError;
 use crate::redstone;
 use crate::state::*;
@@ -5,12 +8,15 @@ use crate::util::*;
 use anchor_lang::prelude::*;
 use zkp_u256::U256;
 
-fn make_price_seed() -> [u8; 32] {
+pub fn make_price_seed() -> [u8; 32] {
     let mut seed = [0u8; 32];
     seed[0..5].copy_from_slice(b"price");
     seed
 }
 
+/// Derived feeds of the feed to recompute are passed in the remaining
+/// accounts, as `[derived feed, derived price account, other source price
+/// account]` triples
 #[derive(Accounts)]
 #[instruction(feed_id: FeedId)]
 pub struct ProcessPayload<'info> {
@@ -23,31 +29,49 @@ pub struct ProcessPayload<'info> {
         space = 8 + std::mem::size_of::<PriceData>(),
         seeds = [
             &make_price_seed(),
-            &feed_id
+            feed_id.as_ref()
         ],
         bump,
         constraint = price_account.to_account_info().owner == __program_id
     )]
-    pub price_account: Account<'info, PriceData>,
+    pub price_account: AccountLoader<'info, PriceData>,
     pub config_account: Account<'info, ConfigAccount>,
+    /// CHECK: optional per-feed overrides, only read when initialized
+    #[account(
+        seeds = [b"feed_config".as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub feed_config: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"registry"],
+        bump,
+        constraint = feed_registry.is_active(&feed_id)
+            @ RedstoneError::FeedNotRegistered
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn process_redstone_payload(
-    ctx: Context<ProcessPayload>,
+pub fn process_redstone_payload<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProcessPayload<'info>>,
     feed_id: FeedId,
     payload: Vec<u8>,
 ) -> Result<()> {
+    let feed_config = FeedConfig::load(&ctx.accounts.feed_config)?;
+    let clock = Clock::get()?;
     // block_timestamp as milis
-    let config = Config {
-        block_timestamp: Clock::get()?.unix_timestamp as u64 * 1000,
-        config_account: &ctx.accounts.config_account,
-    };
+    let config = Config::new(
+        clock.unix_timestamp as u64 * 1000,
+        &ctx.accounts.config_account,
+        feed_config.as_ref(),
+    );
 
     redstone::verify_redstone_marker(&payload)?;
 
     let mut payload = payload;
-    let payload = redstone::parse_raw_payload(&mut payload)?;
+    let mut payload = redstone::parse_raw_payload(&mut payload)?;
+    payload.data_packages =
+        redstone::configured_packages(payload.data_packages, config.signers);
 
     redstone::verify_data_packages(&payload, &config)?;
 
@@ -65,48 +89,188 @@ pub fn process_redstone_payload(
             for data_point in &package.data_points {
                 msg!(
                     "Data point: {} {}",
-                    u256_to_string(&data_point.feed_id),
+                    data_point.feed_id,
                     u256_to_num_string(&data_point.value),
                 );
             }
         }
     }
 
-    let mut values: Vec<U256> = Vec::new();
-
-    let package_timestamp = payload.data_packages[0].timestamp;
     for package in &payload.data_packages {
-        if package_timestamp != package.timestamp {
-            return Err(RedstoneError::TimestampMismatch.into());
-        }
         for data_point in &package.data_points {
             if feed_id != data_point.feed_id {
                 return Err(RedstoneError::UnsupportedFeedId.into());
             }
-            values.push(U256::from_bytes_be(&data_point.value));
         }
     }
-    if ctx.accounts.price_account.timestamp >= package_timestamp {
+    // the first package of every signer
+    let data_packages =
+        redstone::feed_packages(&payload.data_packages, &feed_id);
+    let package_timestamp =
+        redstone::package_timestamp(&data_packages, &config)?;
+    let signed_values = redstone::signed_values(&data_packages, &feed_id);
+    let mut price_account = match ctx.accounts.price_account.load_init() {
+        Ok(price_account) => price_account,
+        Err(_) => ctx.accounts.price_account.load_mut()?,
+    };
+    if price_account.timestamp >= package_timestamp {
+        let skip_if_not_newer = feed_config
+            .as_ref()
+            .and_then(|feed_config| feed_config.skip_if_not_newer)
+            .unwrap_or_default();
+        if skip_if_not_newer {
+            msg!(
+                "{} {}: not newer than {}, skipped",
+                package_timestamp,
+                feed_id,
+                price_account.timestamp
+            );
+            return Ok(());
+        }
         return Err(RedstoneError::TimestampTooOld.into());
     }
 
-    let median_value = median(&values);
-    if let Some(median_value) = &median_value {
-        ctx.accounts.price_account.value = median_value.to_bytes_be();
-    } else {
-        return Err(RedstoneError::MedianCalculationError.into());
+    let aggregated = redstone::aggregate_feed(
+        signed_values,
+        feed_config.as_ref(),
+        &config,
+    )?;
+    let aggregated_value = &aggregated.value;
+    if let Some(feed_config) = &feed_config {
+        redstone::verify_update_interval(
+            &price_account,
+            aggregated_value,
+            config.block_timestamp,
+            feed_config,
+        )?;
+    }
+
+    if price_account.max_jump_bps > 0 {
+        let previous_value = U256::from_bytes_be(&price_account.value);
+        if let Some(jump_bps) =
+            deviation_bps(&previous_value, aggregated_value)
+        {
+            if jump_bps > price_account.max_jump_bps as u64 {
+                price_account.pending_value = aggregated_value.to_bytes_be();
+                price_account.pending_timestamp = package_timestamp;
+                msg!(
+                    "{} {}: jump of {} bps exceeds {} bps, held for review",
+                    package_timestamp,
+                    feed_id,
+                    jump_bps,
+                    price_account.max_jump_bps
+                );
+                return Ok(());
+            }
+        }
     }
 
-    ctx.accounts.price_account.timestamp = package_timestamp;
-    ctx.accounts.price_account.feed_id = feed_id;
-    ctx.accounts.price_account.write_timestamp = config.block_timestamp;
+    price_account.value = aggregated_value.to_bytes_be();
+    price_account.timestamp = package_timestamp;
+    price_account.feed_id = feed_id;
+    price_account.write_timestamp = config.block_timestamp;
+    price_account.pending_value = Value::default();
+    price_account.pending_timestamp = 0;
+    price_account.version = PRICE_DATA_VERSION;
+    price_account.decimals = feed_config
+        .as_ref()
+        .and_then(|feed_config| feed_config.decimals)
+        .unwrap_or(REDSTONE_DECIMALS);
+    price_account.signer_bitmap = aggregated.signer_bitmap;
+    price_account.signer_count = aggregated.signer_bitmap.count_ones() as u8;
+    price_account.lowest_value = aggregated.lowest_value.to_bytes_be();
+    price_account.highest_value = aggregated.highest_value.to_bytes_be();
+    price_account.write_slot = clock.slot;
+    price_account.round_id += 1;
 
     msg!(
-        "{} {}: {}",
-        ctx.accounts.price_account.timestamp,
-        u256_to_string(&feed_id),
-        u256_to_num_string(&ctx.accounts.price_account.value)
+        "{} {}: {} ({} signers, {}..{}, round {})",
+        price_account.timestamp,
+        feed_id,
+        u256_to_num_string(&price_account.value),
+        price_account.signer_count,
+        u256_to_decimal_string(&aggregated.lowest_value),
+        u256_to_decimal_string(&aggregated.highest_value),
+        price_account.round_id
     );
 
+    let derived_feeds = ctx.remaining_accounts.chunks_exact(3);
+    if !derived_feeds.remainder().is_empty() {
+        return Err(RedstoneError::AccountCountMismatch.into());
+    }
+    for accounts in derived_feeds {
+        update_derived_feed(accounts, &price_account, &clock)?;
+    }
+
+    Ok(())
+}
+
+/// Recomputes the derived feed of the `[derived feed, derived price
+/// account, other source price account]` accounts after an update of
+/// `source`
+fn update_derived_feed<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    source: &PriceData,
+    clock: &Clock,
+) -> Result<()> {
+    let [derived_feed, price_account, other_source] = accounts else {
+        return Err(RedstoneError::AccountCountMismatch.into());
+    };
+    let derived_feed = Account::<DerivedFeed>::try_from(derived_feed)?;
+    let other_feed_id = derived_feed
+        .other_source(&source.feed_id)
+        .ok_or(RedstoneError::InvalidDerivedFeed)?;
+    let other = load_price_data(other_source, &other_feed_id)?;
+    if other.round_id == 0 {
+        msg!(
+            "{}: {} has no price yet, skipped",
+            derived_feed.feed_id,
+            other_feed_id
+        );
+        return Ok(());
+    }
+    let (numerator, denominator) = if source.feed_id == derived_feed.numerator
+    {
+        (source, &other)
+    } else {
+        (&other, source)
+    };
+    // the source update stands even if the cross rate cannot be computed
+    let value = match decimals::ratio(
+        &U256::from_bytes_be(&numerator.value),
+        numerator.decimals,
+        &U256::from_bytes_be(&denominator.value),
+        denominator.decimals,
+        derived_feed.decimals,
+    ) {
+        Ok(value) => value,
+        Err(err) => {
+            msg!("{}: {}, skipped", derived_feed.feed_id, err);
+            return Ok(());
+        }
+    };
+
+    if price_account.key != &price_account_address(&derived_feed.feed_id) {
+        return Err(RedstoneError::InvalidPriceAccount.into());
+    }
+    let price_account = AccountLoader::<PriceData>::try_from(price_account)?;
+    let mut price_data = price_account.load_mut()?;
+    price_data.value = value.to_bytes_be();
+    // as stale as the older source
+    price_data.timestamp = numerator.timestamp.min(denominator.timestamp);
+    price_data.write_timestamp = clock.unix_timestamp as u64 * 1000;
+    price_data.decimals = derived_feed.decimals;
+    price_data.signer_count =
+        numerator.signer_count.min(denominator.signer_count);
+    price_data.write_slot = clock.slot;
+    price_data.round_id += 1;
+
+    msg!(
+        "{} {}: {} (round {})",
+        price_data.timestamp,
+        derived_feed.feed_id,
+        u256_to_decimal_string(&value),
+        price_data.round_id
+    );
     Ok(())
 }
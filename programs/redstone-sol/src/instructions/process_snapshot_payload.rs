@@ -0,0 +1,137 @@
+use crate::constants::REDSTONE_DECIMALS;
+use crate::error::RedstoneError;
+use crate::redstone;
+use crate::state::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+
+/// Feed configs of the snapshot feeds are passed in the remaining accounts,
+/// in the order of the snapshot entries
+#[derive(Accounts)]
+#[instruction(group_id: FeedId)]
+pub struct ProcessSnapshotPayload<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        seeds = [b"registry"],
+        bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+    #[account(
+        mut,
+        seeds = [b"snapshot".as_ref(), group_id.as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, PriceSnapshot>,
+}
+
+/// Writes all of the snapshot prices from a single payload. Every feed is
+/// verified and aggregated as set in its feed config, reaching its own
+/// signer threshold; the snapshot takes the oldest of their timestamps
+pub fn process_snapshot_payload<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProcessSnapshotPayload<'info>>,
+    group_id: FeedId,
+    payload: Vec<u8>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let config_account = &ctx.accounts.config_account;
+    // block_timestamp as milis
+    let block_timestamp = clock.unix_timestamp as u64 * 1000;
+
+    redstone::verify_redstone_marker(&payload)?;
+
+    let mut payload = payload;
+    let mut payload = redstone::parse_raw_payload(&mut payload)?;
+    payload.data_packages = redstone::configured_packages(
+        payload.data_packages,
+        &config_account.signers,
+    );
+
+    let snapshot = &mut ctx.accounts.snapshot;
+    if ctx.remaining_accounts.len() != snapshot.entries.len() {
+        return Err(RedstoneError::AccountCountMismatch.into());
+    }
+    for package in &payload.data_packages {
+        for data_point in &package.data_points {
+            if snapshot.entry(&data_point.feed_id).is_none() {
+                return Err(RedstoneError::UnsupportedFeedId.into());
+            }
+        }
+    }
+
+    let mut entries = Vec::with_capacity(snapshot.entries.len());
+    let mut timestamp = u64::MAX;
+    for (entry, feed_config) in
+        snapshot.entries.iter().zip(ctx.remaining_accounts)
+    {
+        let feed_id = entry.feed_id;
+        if !ctx.accounts.feed_registry.is_active(&feed_id) {
+            return Err(RedstoneError::FeedNotRegistered.into());
+        }
+        let (feed_config_address, _) = Pubkey::find_program_address(
+            &[b"feed_config", feed_id.as_ref()],
+            &crate::ID,
+        );
+        if feed_config.key != &feed_config_address {
+            return Err(RedstoneError::InvalidFeedConfig.into());
+        }
+        let feed_config = FeedConfig::load(feed_config)?;
+        let feed_config = feed_config.as_ref();
+        let config =
+            Config::new(block_timestamp, config_account, feed_config);
+
+        let feed_payload = Payload {
+            data_packages: redstone::feed_packages(
+                &payload.data_packages,
+                &feed_id,
+            ),
+        };
+        redstone::verify_data_packages(&feed_payload, &config)?;
+        let package_timestamp = redstone::package_timestamp(
+            &feed_payload.data_packages,
+            &config,
+        )?;
+        let aggregated = redstone::aggregate_feed(
+            redstone::signed_values(&feed_payload.data_packages, &feed_id),
+            feed_config,
+            &config,
+        )?;
+
+        timestamp = timestamp.min(package_timestamp);
+        entries.push(SnapshotEntry {
+            feed_id,
+            value: aggregated.value.to_bytes_be(),
+            decimals: feed_config
+                .and_then(|feed_config| feed_config.decimals)
+                .unwrap_or(REDSTONE_DECIMALS),
+            signer_count: aggregated.signer_bitmap.count_ones() as u8,
+        });
+        msg!(
+            "{} {}: {}",
+            package_timestamp,
+            feed_id,
+            u256_to_decimal_string(&aggregated.value)
+        );
+    }
+    if snapshot.timestamp >= timestamp {
+        return Err(RedstoneError::TimestampTooOld.into());
+    }
+
+    snapshot.entries = entries;
+    snapshot.timestamp = timestamp;
+    snapshot.write_timestamp = block_timestamp;
+    snapshot.write_slot = clock.slot;
+    snapshot.round_id += 1;
+    msg!(
+        "{} snapshot {}: round {}",
+        snapshot.timestamp,
+        group_id,
+        snapshot.round_id
+    );
+
+    Ok(())
+}
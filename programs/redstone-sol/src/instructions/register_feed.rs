@@ -0,0 +1,60 @@
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RegisterFeed<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump,
+        realloc = FeedRegistry::space(feed_registry.feeds.len() + 1),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+    /// CHECK: the `["derived_feed", feed id]` PDA, verified by the handler
+    /// as the feed id is parsed from the name
+    pub derived_feed: UncheckedAccount<'info>,
+    /// CHECK: the `["basket_feed", feed id]` PDA, verified by the handler
+    pub basket_feed: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn register_feed(ctx: Context<RegisterFeed>, name: String) -> Result<()> {
+    let feed_id: FeedId = name.parse()?;
+    let feed_registry = &mut ctx.accounts.feed_registry;
+    if feed_registry.contains(&feed_id) {
+        return Err(RedstoneError::FeedAlreadyRegistered.into());
+    }
+    // derived and basket feeds own the price account of their id
+    for (seed, account) in [
+        (b"derived_feed".as_ref(), &ctx.accounts.derived_feed),
+        (b"basket_feed".as_ref(), &ctx.accounts.basket_feed),
+    ] {
+        let (address, _) = Pubkey::find_program_address(
+            &[seed, feed_id.as_ref()],
+            &crate::ID,
+        );
+        if account.key != &address {
+            return Err(ErrorCode::ConstraintSeeds.into());
+        }
+        if account.owner == &crate::ID && !account.data_is_empty() {
+            return Err(RedstoneError::FeedIdInUse.into());
+        }
+    }
+    feed_registry.feeds.push(RegisteredFeed {
+        feed_id,
+        active: true,
+    });
+    msg!("Registered feed {}", feed_id);
+    Ok(())
+}
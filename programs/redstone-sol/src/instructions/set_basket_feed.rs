@@ -0,0 +1,106 @@
+use crate::constants::{MAX_BASKET_COMPONENTS, PRICE_DATA_VERSION};
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+use super::make_price_seed;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct SetBasketFeed<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        seeds = [b"registry"],
+        bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        // sized for any definition, so that it can be replaced in place
+        space = BasketFeed::space(MAX_BASKET_COMPONENTS),
+        seeds = [b"basket_feed".as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub basket_feed: Account<'info, BasketFeed>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + std::mem::size_of::<PriceData>(),
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump,
+        constraint = price_account.to_account_info().owner == __program_id
+    )]
+    pub price_account: AccountLoader<'info, PriceData>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates or replaces the definition of the basket `feed_id`. Its price
+/// is cleared until the next `update_basket_feed`
+pub fn set_basket_feed(
+    ctx: Context<SetBasketFeed>,
+    feed_id: FeedId,
+    components: Vec<BasketComponent>,
+    weight_decimals: u8,
+    decimals: u8,
+    max_component_age_ms: u64,
+) -> Result<()> {
+    let feed_registry = &ctx.accounts.feed_registry;
+    // basket prices are never written from payloads
+    if components.is_empty()
+        || components.len() > MAX_BASKET_COMPONENTS
+        || feed_registry.contains(&feed_id)
+    {
+        return Err(RedstoneError::InvalidBasketFeed.into());
+    }
+    for (i, component) in components.iter().enumerate() {
+        if component.weight == 0
+            || components[..i]
+                .iter()
+                .any(|other| other.feed_id == component.feed_id)
+        {
+            return Err(RedstoneError::InvalidBasketFeed.into());
+        }
+        if !feed_registry.contains(&component.feed_id) {
+            return Err(RedstoneError::FeedNotRegistered.into());
+        }
+    }
+
+    let basket_feed = &mut ctx.accounts.basket_feed;
+    // the price account of a new basket must be new as well, it would
+    // otherwise take over the price of a derived feed of the same id
+    let replaced = basket_feed.feed_id == feed_id;
+    let mut price_account = match ctx.accounts.price_account.load_init() {
+        Ok(price_account) => price_account,
+        Err(_) if replaced => ctx.accounts.price_account.load_mut()?,
+        Err(_) => return Err(RedstoneError::FeedIdInUse.into()),
+    };
+    basket_feed.feed_id = feed_id;
+    basket_feed.decimals = decimals;
+    basket_feed.weight_decimals = weight_decimals;
+    basket_feed.max_component_age_ms = max_component_age_ms;
+    basket_feed.components = components;
+
+    price_account.feed_id = feed_id;
+    price_account.value = Value::default();
+    price_account.timestamp = 0;
+    price_account.version = PRICE_DATA_VERSION;
+    price_account.decimals = decimals;
+    price_account.signer_count = 0;
+    msg!(
+        "Set basket {} of {} feeds",
+        feed_id,
+        basket_feed.components.len()
+    );
+    Ok(())
+}
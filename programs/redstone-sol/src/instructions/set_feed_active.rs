@@ -0,0 +1,39 @@
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetFeedActive<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump
+    )]
+    pub feed_registry: Account<'info, FeedRegistry>,
+}
+
+pub fn set_feed_active(
+    ctx: Context<SetFeedActive>,
+    feed_id: FeedId,
+    active: bool,
+) -> Result<()> {
+    let feed = ctx
+        .accounts
+        .feed_registry
+        .find_mut(&feed_id)
+        .ok_or(RedstoneError::FeedNotRegistered)?;
+    feed.active = active;
+    msg!(
+        "Feed {} is now {}",
+        feed_id,
+        if active { "active" } else { "inactive" }
+    );
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use crate::decimals::MAX_POW10;
+use crate::error::RedstoneError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct SetFeedConfig<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = FeedConfig::space(),
+        seeds = [b"feed_config".as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub feed_config: Account<'info, FeedConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Overrides of a feed config, see `FeedConfig`; `None` falls back to
+/// `ConfigAccount` or the default behaviour
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct FeedConfigParams {
+    pub signer_count_threshold: Option<u8>,
+    pub max_timestamp_delay_ms: Option<u64>,
+    pub max_timestamp_ahead_ms: Option<u64>,
+    pub decimals: Option<u8>,
+    pub min_value: Option<Value>,
+    pub max_value: Option<Value>,
+    pub aggregation: Option<Aggregation>,
+    pub outlier_filter: Option<OutlierFilter>,
+    pub max_package_timestamp_skew_ms: Option<u64>,
+    pub timestamp_aggregation: Option<TimestampAggregation>,
+    pub skip_if_not_newer: Option<bool>,
+    pub min_update_interval_ms: Option<u64>,
+    pub update_deviation_bps: Option<u64>,
+}
+
+/// Overwrites all of the overrides
+pub fn set_feed_config(
+    ctx: Context<SetFeedConfig>,
+    feed_id: FeedId,
+    params: FeedConfigParams,
+) -> Result<()> {
+    let FeedConfigParams {
+        signer_count_threshold,
+        max_timestamp_delay_ms,
+        max_timestamp_ahead_ms,
+        decimals,
+        min_value,
+        max_value,
+        aggregation,
+        outlier_filter,
+        max_package_timestamp_skew_ms,
+        timestamp_aggregation,
+        skip_if_not_newer,
+        min_update_interval_ms,
+        update_deviation_bps,
+    } = params;
+    if let (Some(min_value), Some(max_value)) = (&min_value, &max_value) {
+        // big-endian byte arrays compare like the numbers they encode
+        if min_value > max_value {
+            return Err(RedstoneError::InvalidFeedConfig.into());
+        }
+    }
+    if decimals.is_some_and(|decimals| decimals > MAX_POW10)
+        || aggregation.is_some_and(|aggregation| !aggregation.is_valid())
+        || outlier_filter.is_some_and(|filter| !filter.is_valid())
+    {
+        return Err(RedstoneError::InvalidFeedConfig.into());
+    }
+
+    ctx.accounts.feed_config.set_inner(FeedConfig {
+        feed_id,
+        signer_count_threshold,
+        max_timestamp_delay_ms,
+        max_timestamp_ahead_ms,
+        decimals,
+        min_value,
+        max_value,
+        aggregation,
+        outlier_filter,
+        max_package_timestamp_skew_ms,
+        timestamp_aggregation,
+        skip_if_not_newer,
+        min_update_interval_ms,
+        update_deviation_bps,
+    });
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+use super::make_price_seed;
+
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct SetMaxJumpBps<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = owner
+    )]
+    pub config_account: Account<'info, ConfigAccount>,
+    #[account(
+        mut,
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump
+    )]
+    pub price_account: AccountLoader<'info, PriceData>,
+}
+
+pub fn set_max_jump_bps(
+    ctx: Context<SetMaxJumpBps>,
+    _feed_id: FeedId,
+    max_jump_bps: u16,
+) -> Result<()> {
+    ctx.accounts.price_account.load_mut()?.max_jump_bps = max_jump_bps;
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::consumer::load_price_at;
+use crate::decimals;
+use crate::error::RedstoneError;
+use crate::state::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use zkp_u256::U256;
+
+use super::make_price_seed;
+
+/// Price accounts of the basket components are passed in the remaining
+/// accounts, in the order of the components
+#[derive(Accounts)]
+#[instruction(feed_id: FeedId)]
+pub struct UpdateBasketFeed<'info> {
+    #[account(
+        seeds = [b"basket_feed".as_ref(), feed_id.as_ref()],
+        bump
+    )]
+    pub basket_feed: Account<'info, BasketFeed>,
+    #[account(
+        mut,
+        seeds = [
+            &make_price_seed(),
+            feed_id.as_ref()
+        ],
+        bump
+    )]
+    pub price_account: AccountLoader<'info, PriceData>,
+}
+
+/// Recomputes the basket from the current component prices, each of them
+/// has to be at most `BasketFeed::max_component_age_ms` old
+pub fn update_basket_feed<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateBasketFeed<'info>>,
+    feed_id: FeedId,
+) -> Result<()> {
+    let basket_feed = &ctx.accounts.basket_feed;
+    if ctx.remaining_accounts.len() != basket_feed.components.len() {
+        return Err(RedstoneError::AccountCountMismatch.into());
+    }
+    let clock = Clock::get()?;
+    let now_ms = clock.unix_timestamp as u64 * 1000;
+
+    let prices = basket_feed
+        .components
+        .iter()
+        .zip(ctx.remaining_accounts)
+        .map(|(component, account)| {
+            load_price_at(
+                account,
+                &component.feed_id,
+                basket_feed.max_component_age_ms,
+                now_ms,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let components: Vec<(U256, u8, u64)> = prices
+        .iter()
+        .zip(&basket_feed.components)
+        .map(|(price, component)| {
+            (
+                U256::from_bytes_be(&price.value),
+                price.decimals,
+                component.weight,
+            )
+        })
+        .collect();
+    let value = decimals::weighted_sum(
+        &components,
+        basket_feed.weight_decimals,
+        basket_feed.decimals,
+    )?;
+
+    // as stale as the oldest component
+    let timestamp = prices
+        .iter()
+        .map(|price| price.timestamp)
+        .min()
+        .unwrap_or_default();
+
+    let mut price_account = ctx.accounts.price_account.load_mut()?;
+    let value = value.to_bytes_be();
+    if price_account.value == value && price_account.timestamp == timestamp {
+        msg!("{} {}: unchanged, skipped", timestamp, feed_id);
+        return Ok(());
+    }
+    price_account.value = value;
+    price_account.timestamp = timestamp;
+    price_account.write_timestamp = now_ms;
+    price_account.decimals = basket_feed.decimals;
+    price_account.signer_count = prices
+        .iter()
+        .map(|price| price.signer_count)
+        .min()
+        .unwrap_or_default();
+    price_account.write_slot = clock.slot;
+    price_account.round_id += 1;
+
+    msg!(
+        "{} {}: {} (round {})",
+        price_account.timestamp,
+        feed_id,
+        u256_to_num_string(&price_account.value),
+        price_account.round_id
+    );
+    Ok(())
+}
@@ -1,5 +1,10 @@
+pub mod aggregation;
 pub mod constants;
+pub mod consumer;
+pub mod decimals;
 pub mod error;
+pub mod events;
+pub mod feed_id;
 pub mod instructions;
 pub mod redstone;
 pub mod state;
@@ -11,19 +16,25 @@ use state::*;
 
 declare_id!("CbKWhF5f2aHFY8t7s1pwrX48jYWcFPcChWztbZm4v3oa");
 
+// shadows the CPI accounts Anchor generates for the empty `GetPrices`
+#[cfg(feature = "cpi")]
+mod __cpi_client_accounts_get_prices {
+    pub use crate::instructions::GetPricesCpiAccounts as GetPrices;
+}
+
 #[program]
 pub mod redstone_sol {
     use super::*;
 
-    pub fn process_redstone_payload(
-        ctx: Context<ProcessPayload>,
+    pub fn process_redstone_payload<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessPayload<'info>>,
         feed_id: FeedId,
         payload: Vec<u8>,
     ) -> Result<()> {
         msg!(
             "Processing redstone payload of size {} for {}",
             payload.len(),
-            util::u256_to_string(&feed_id).to_string()
+            feed_id
         );
         instructions::process_redstone_payload(ctx, feed_id, payload)
     }
@@ -35,12 +46,16 @@ pub mod redstone_sol {
         max_timestamp_delay_ms: u64,
         max_timestamp_ahead_ms: u64,
     ) -> Result<()> {
+        if signers.len() > constants::MAX_SIGNERS {
+            return Err(error::RedstoneError::TooManySigners.into());
+        }
         let config_account = &mut ctx.accounts.config_account;
         config_account.owner = ctx.accounts.owner.key();
         config_account.signers = signers;
         config_account.signer_count_threshold = signer_count_threshold;
         config_account.max_timestamp_delay_ms = max_timestamp_delay_ms;
         config_account.max_timestamp_ahead_ms = max_timestamp_ahead_ms;
+        config_account.version = constants::CONFIG_ACCOUNT_VERSION;
         Ok(())
     }
     pub fn update_config(
@@ -52,6 +67,9 @@ pub mod redstone_sol {
     ) -> Result<()> {
         let config_account = &mut ctx.accounts.config_account;
         if let Some(signers) = signers {
+            if signers.len() > constants::MAX_SIGNERS {
+                return Err(error::RedstoneError::TooManySigners.into());
+            }
             config_account.signers = signers;
         }
         if let Some(threshold) = signer_count_threshold {
@@ -63,19 +81,162 @@ pub mod redstone_sol {
         if let Some(ahead) = max_timestamp_ahead_ms {
             config_account.max_timestamp_ahead_ms = ahead;
         }
+        // rewritten in the current layout, sized to the signer list
+        config_account.version = constants::CONFIG_ACCOUNT_VERSION;
         Ok(())
     }
+
+    pub fn set_max_jump_bps(
+        ctx: Context<SetMaxJumpBps>,
+        feed_id: FeedId,
+        max_jump_bps: u16,
+    ) -> Result<()> {
+        instructions::set_max_jump_bps(ctx, feed_id, max_jump_bps)
+    }
+
+    pub fn accept_pending_price(
+        ctx: Context<AcceptPendingPrice>,
+        feed_id: FeedId,
+    ) -> Result<()> {
+        instructions::accept_pending_price(ctx, feed_id)
+    }
+
+    pub fn set_feed_config(
+        ctx: Context<SetFeedConfig>,
+        feed_id: FeedId,
+        params: FeedConfigParams,
+    ) -> Result<()> {
+        instructions::set_feed_config(ctx, feed_id, params)
+    }
+
+    pub fn initialize_feed_registry(
+        _ctx: Context<InitializeFeedRegistry>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn register_feed(
+        ctx: Context<RegisterFeed>,
+        name: String,
+    ) -> Result<()> {
+        instructions::register_feed(ctx, name)
+    }
+
+    pub fn set_feed_active(
+        ctx: Context<SetFeedActive>,
+        feed_id: FeedId,
+        active: bool,
+    ) -> Result<()> {
+        instructions::set_feed_active(ctx, feed_id, active)
+    }
+
+    pub fn close_price_account(
+        ctx: Context<ClosePriceAccount>,
+        feed_id: FeedId,
+    ) -> Result<()> {
+        instructions::close_price_account(ctx, feed_id)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate_config(ctx)
+    }
+
+    pub fn migrate_price_account(
+        ctx: Context<MigratePriceAccount>,
+        feed_id: FeedId,
+    ) -> Result<()> {
+        instructions::migrate_price_account(ctx, feed_id)
+    }
+
+    pub fn latest_round_data(
+        ctx: Context<LatestRoundData>,
+        feed_id: FeedId,
+    ) -> Result<RoundData> {
+        instructions::latest_round_data(ctx, feed_id)
+    }
+
+    pub fn get_prices<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetPrices>,
+        feed_ids: Vec<FeedId>,
+        max_age_ms: Option<u64>,
+    ) -> Result<Vec<PriceEntry>> {
+        instructions::get_prices(ctx, feed_ids, max_age_ms)
+    }
+
+    pub fn create_snapshot(
+        ctx: Context<CreateSnapshot>,
+        group_id: FeedId,
+        feed_ids: Vec<FeedId>,
+    ) -> Result<()> {
+        instructions::create_snapshot(ctx, group_id, feed_ids)
+    }
+
+    pub fn process_snapshot_payload<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessSnapshotPayload<'info>>,
+        group_id: FeedId,
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        instructions::process_snapshot_payload(ctx, group_id, payload)
+    }
+
+    pub fn create_derived_feed(
+        ctx: Context<CreateDerivedFeed>,
+        feed_id: FeedId,
+        numerator: FeedId,
+        denominator: FeedId,
+        decimals: u8,
+    ) -> Result<()> {
+        instructions::create_derived_feed(
+            ctx,
+            feed_id,
+            numerator,
+            denominator,
+            decimals,
+        )
+    }
+
+    pub fn set_basket_feed(
+        ctx: Context<SetBasketFeed>,
+        feed_id: FeedId,
+        components: Vec<BasketComponent>,
+        weight_decimals: u8,
+        decimals: u8,
+        max_component_age_ms: u64,
+    ) -> Result<()> {
+        instructions::set_basket_feed(
+            ctx,
+            feed_id,
+            components,
+            weight_decimals,
+            decimals,
+            max_component_age_ms,
+        )
+    }
+
+    pub fn update_basket_feed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateBasketFeed<'info>>,
+        feed_id: FeedId,
+    ) -> Result<()> {
+        instructions::update_basket_feed(ctx, feed_id)
+    }
+
+    pub fn migrate_feed_config(
+        ctx: Context<MigrateFeedConfig>,
+        feed_id: FeedId,
+    ) -> Result<()> {
+        instructions::migrate_feed_config(ctx, feed_id)
+    }
 }
 
 #[derive(Accounts)]
+#[instruction(signers: Vec<SignerAddress>)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     #[account(
         init,
         payer = owner,
-        // leaving some excess space for future use
-        space = 8 + std::mem::size_of::<ConfigAccount>() + 32 * 10,
+        space = ConfigAccount::space(signers.len()),
         seeds = [b"config"],
         bump
     )]
@@ -84,13 +245,22 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(signers: Option<Vec<SignerAddress>>)]
 pub struct UpdateConfig<'info> {
+    #[account(mut)]
     pub owner: Signer<'info>,
     #[account(
         mut,
         seeds = [b"config"],
         bump,
-        has_one = owner
+        has_one = owner,
+        // sized to the signer list, the owner pays or receives the difference
+        realloc = ConfigAccount::space(
+            signers.as_ref().map_or(config_account.signers.len(), Vec::len)
+        ),
+        realloc::payer = owner,
+        realloc::zero = false
     )]
     pub config_account: Account<'info, ConfigAccount>,
+    pub system_program: Program<'info, System>,
 }
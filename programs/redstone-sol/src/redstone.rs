@@ -2,9 +2,11 @@ use std::collections::HashSet;
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::secp256k1_recover::secp256k1_recover;
+use zkp_u256::U256;
 
 use crate::constants::*;
 use crate::error::RedstoneError;
+use crate::events::PriceOutOfBounds;
 use crate::state::*;
 use crate::util::*;
 
@@ -117,7 +119,7 @@ fn parse_data_point(payload: &mut Vec<u8>, value_size: usize) -> DataPoint {
     let feed_id = payload.trim_end(DATA_FEED_ID_BS);
     DataPoint {
         value: value.try_into().unwrap(),
-        feed_id: feed_id.try_into().unwrap(),
+        feed_id: FeedId(feed_id.try_into().unwrap()),
     }
 }
 
@@ -170,6 +172,77 @@ pub fn recover_address(
     }
 }
 
+/// Keeps the packages of configured signers, so that packages signed by
+/// anyone else never reach the aggregation
+pub fn configured_packages(
+    data_packages: Vec<DataPackage>,
+    signers: &[SignerAddress],
+) -> Vec<DataPackage> {
+    data_packages
+        .into_iter()
+        .filter(|package| {
+            let configured = signers.contains(&package.signer_address);
+            if !configured {
+                msg!(
+                    "Ignored package of 0x{}",
+                    bytes_to_hex(&package.signer_address)
+                );
+            }
+            configured
+        })
+        .collect()
+}
+
+/// Values of `feed_id` in the packages, the first one of every signer
+pub fn signed_values(
+    data_packages: &[DataPackage],
+    feed_id: &FeedId,
+) -> Vec<(SignerAddress, U256)> {
+    feed_packages(data_packages, feed_id)
+        .into_iter()
+        .map(|package| {
+            (
+                package.signer_address,
+                U256::from_bytes_be(&package.data_points[0].value),
+            )
+        })
+        .collect()
+}
+
+/// Packages carrying `feed_id`, reduced to its value, the first one of
+/// every signer
+pub fn feed_packages(
+    data_packages: &[DataPackage],
+    feed_id: &FeedId,
+) -> Vec<DataPackage> {
+    let mut seen_signers: HashSet<SignerAddress> = HashSet::new();
+    data_packages
+        .iter()
+        .filter_map(|package| {
+            let data_point = package
+                .data_points
+                .iter()
+                .find(|data_point| &data_point.feed_id == feed_id)?;
+            if !seen_signers.insert(package.signer_address) {
+                msg!(
+                    "Ignored duplicate {} package of 0x{}",
+                    feed_id,
+                    bytes_to_hex(&package.signer_address)
+                );
+                return None;
+            }
+            Some(DataPackage {
+                signer_address: package.signer_address,
+                timestamp: package.timestamp,
+                data_points: vec![DataPoint {
+                    feed_id: *feed_id,
+                    value: data_point.value,
+                }],
+            })
+        })
+        .collect()
+}
+
 pub fn verify_data_packages(
     payload: &Payload,
     config: &Config,
@@ -179,35 +252,30 @@ pub fn verify_data_packages(
     }
     verify_signer_count(
         &payload.data_packages,
-        config.config_account.signer_count_threshold,
-        &config.config_account.signers,
+        config.signer_count_threshold,
+        config.signers,
     )?;
     Ok(())
 }
 
 pub fn verify_timestamp(timestamp: u64, config: &Config) -> Result<()> {
-    if timestamp + config.config_account.max_timestamp_delay_ms
-        < config.block_timestamp
-    {
+    if timestamp + config.max_timestamp_delay_ms < config.block_timestamp {
         #[cfg(feature = "dev")]
         msg!(
             "Timestamp: {} + {} < {}",
             timestamp,
-            config.config_account.max_timestamp_delay_ms,
+            config.max_timestamp_delay_ms,
             config.block_timestamp
         );
         return Err(RedstoneError::TimestampTooOld.into());
     }
-    if timestamp
-        > config.block_timestamp
-            + config.config_account.max_timestamp_ahead_ms
-    {
+    if timestamp > config.block_timestamp + config.max_timestamp_ahead_ms {
         #[cfg(feature = "dev")]
         msg!(
             "Timestamp: {} > {} + {}",
             timestamp,
             config.block_timestamp,
-            config.config_account.max_timestamp_ahead_ms
+            config.max_timestamp_ahead_ms
         );
         return Err(RedstoneError::TimestampTooFuture.into());
     }
@@ -219,19 +287,217 @@ pub fn verify_signer_count(
     threshold: u8,
     signers: &[SignerAddress],
 ) -> Result<()> {
-    let unique_signers: HashSet<SignerAddress> =
-        HashSet::from_iter(signers.iter().copied());
-    let mut count: u8 = 0;
-    for package in data_packages {
-        if unique_signers.contains(&package.signer_address) {
-            count += 1;
+    // distinct configured signers, duplicate packages count once
+    let count = signer_bitmap(
+        data_packages.iter().map(|package| &package.signer_address),
+        signers,
+    )
+    .count_ones();
+    if count >= threshold as u32 {
+        return Ok(());
+    }
+    msg!("Signer count: {} < {}", count, threshold);
+    Err(RedstoneError::InsufficientSignerCount.into())
+}
+
+/// Timestamp of the payload, failing if the package timestamps are further
+/// apart than `max_package_timestamp_skew_ms`
+pub fn package_timestamp(
+    data_packages: &[DataPackage],
+    config: &Config,
+) -> Result<u64> {
+    let timestamps: Vec<u64> = data_packages
+        .iter()
+        .map(|package| package.timestamp)
+        .collect();
+    let (Some(oldest), Some(newest)) =
+        (timestamps.iter().min(), timestamps.iter().max())
+    else {
+        return Err(RedstoneError::TimestampMismatch.into());
+    };
+    if newest - oldest > config.max_package_timestamp_skew_ms {
+        msg!(
+            "Package timestamps: {} - {} > {}",
+            newest,
+            oldest,
+            config.max_package_timestamp_skew_ms
+        );
+        return Err(RedstoneError::TimestampMismatch.into());
+    }
+    config
+        .timestamp_aggregation
+        .aggregate(&timestamps)
+        .ok_or(RedstoneError::TimestampMismatch.into())
+}
+
+/// Value of a feed aggregated from its signed values
+pub struct AggregatedValue {
+    pub value: U256,
+    /// Signers of the values left after the outlier filter
+    pub signer_bitmap: u32,
+    pub lowest_value: U256,
+    pub highest_value: U256,
+}
+
+/// Filters and aggregates the signed values of a feed as set in its
+/// config, verifying the value bounds of the result
+pub fn aggregate_feed(
+    signed_values: Vec<(SignerAddress, U256)>,
+    feed_config: Option<&FeedConfig>,
+    config: &Config,
+) -> Result<AggregatedValue> {
+    let feed_config = feed_config.cloned().unwrap_or_default();
+    let signed_values = match &feed_config.outlier_filter {
+        Some(outlier_filter) => {
+            filter_outliers(signed_values, outlier_filter, config)?
         }
-        if count >= threshold {
+        None => signed_values,
+    };
+    let values: Vec<U256> = signed_values
+        .iter()
+        .map(|(_, value)| value.clone())
+        .collect();
+    let value = feed_config
+        .aggregation
+        .unwrap_or_default()
+        .aggregate(&values)
+        .ok_or(RedstoneError::MedianCalculationError)?;
+    verify_value_bounds(&value, &feed_config)?;
+
+    // values is non-empty, the aggregation succeeded
+    Ok(AggregatedValue {
+        value,
+        signer_bitmap: signer_bitmap(
+            signed_values.iter().map(|(signer, _)| signer),
+            config.signers,
+        ),
+        lowest_value: values.iter().min().unwrap().clone(),
+        highest_value: values.iter().max().unwrap().clone(),
+    })
+}
+
+/// Bitmap of the configured signers found among the package signers, bit
+/// `i` is set when `signers[i]` signed a package
+pub fn signer_bitmap<'a>(
+    package_signers: impl IntoIterator<Item = &'a SignerAddress>,
+    signers: &[SignerAddress],
+) -> u32 {
+    package_signers.into_iter().fold(
+        0,
+        |bitmap, package_signer| match signers
+            .iter()
+            .position(|signer| signer == package_signer)
+        {
+            Some(index) if index < MAX_SIGNERS => bitmap | 1 << index,
+            _ => bitmap,
+        },
+    )
+}
+
+/// Drops the values rejected by `outlier_filter`, failing if the signers
+/// of the remaining ones no longer reach the threshold
+pub fn filter_outliers(
+    signed_values: Vec<(SignerAddress, U256)>,
+    outlier_filter: &OutlierFilter,
+    config: &Config,
+) -> Result<Vec<(SignerAddress, U256)>> {
+    let values: Vec<U256> = signed_values
+        .iter()
+        .map(|(_, value)| value.clone())
+        .collect();
+    let keep = outlier_filter
+        .keep(&values)
+        .ok_or(RedstoneError::MedianCalculationError)?;
+
+    let mut kept_values = Vec::with_capacity(signed_values.len());
+    for ((signer, value), keep) in signed_values.into_iter().zip(keep) {
+        if keep {
+            kept_values.push((signer, value));
+        } else {
+            msg!(
+                "Dropped outlier of 0x{}: {}",
+                bytes_to_hex(&signer),
+                u256_to_decimal_string(&value)
+            );
+        }
+    }
+
+    let signer_count = signer_bitmap(
+        kept_values.iter().map(|(signer, _)| signer),
+        config.signers,
+    )
+    .count_ones();
+    if signer_count < config.signer_count_threshold as u32 {
+        msg!(
+            "Signer count after filtering: {} < {}",
+            signer_count,
+            config.signer_count_threshold
+        );
+        return Err(RedstoneError::InsufficientSignerCount.into());
+    }
+    Ok(kept_values)
+}
+
+/// Rejects writes within `min_update_interval_ms` of the previous one,
+/// unless the value moved by at least `update_deviation_bps`
+pub fn verify_update_interval(
+    price_data: &PriceData,
+    value: &U256,
+    block_timestamp: u64,
+    feed_config: &FeedConfig,
+) -> Result<()> {
+    let Some(min_update_interval_ms) = feed_config.min_update_interval_ms
+    else {
+        return Ok(());
+    };
+    // nothing written yet
+    if price_data.write_timestamp == 0 {
+        return Ok(());
+    }
+    let elapsed = block_timestamp.saturating_sub(price_data.write_timestamp);
+    if elapsed >= min_update_interval_ms {
+        return Ok(());
+    }
+    let deviation =
+        deviation_bps(&U256::from_bytes_be(&price_data.value), value);
+    if let (Some(deviation), Some(update_deviation_bps)) =
+        (deviation, feed_config.update_deviation_bps)
+    {
+        if deviation >= update_deviation_bps {
             return Ok(());
         }
     }
-    msg!("Signer count: {} < {}", count, threshold);
-    Err(RedstoneError::InsufficientSignerCount.into())
+    msg!("Update interval: {} < {}", elapsed, min_update_interval_ms);
+    Err(RedstoneError::UpdateTooFrequent.into())
+}
+
+/// Rejects values outside of the feed config bounds, emitting
+/// `PriceOutOfBounds`
+pub fn verify_value_bounds(
+    value: &U256,
+    feed_config: &FeedConfig,
+) -> Result<()> {
+    let below_min = feed_config
+        .min_value
+        .is_some_and(|min_value| *value < U256::from_bytes_be(&min_value));
+    let above_max = feed_config
+        .max_value
+        .is_some_and(|max_value| *value > U256::from_bytes_be(&max_value));
+    if !below_min && !above_max {
+        return Ok(());
+    }
+    if below_min {
+        msg!("Value below minimum: {}", u256_to_decimal_string(value));
+    } else {
+        msg!("Value above maximum: {}", u256_to_decimal_string(value));
+    }
+    emit!(PriceOutOfBounds {
+        feed_id: feed_config.feed_id,
+        value: value.to_bytes_be(),
+        min_value: feed_config.min_value,
+        max_value: feed_config.max_value,
+    });
+    Err(RedstoneError::ValueOutOfBounds.into())
 }
 
 pub fn keccak256(data: &[u8]) -> [u8; 32] {
@@ -241,6 +507,8 @@ pub fn keccak256(data: &[u8]) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bytemuck::Zeroable;
+
     #[test]
     fn test_recover_address() {
         let signature = [
@@ -263,4 +531,268 @@ mod tests {
         println!("{:?}", address);
         SIGNERS.iter().find(|&x| *x == address).unwrap();
     }
+
+    #[test]
+    fn test_signer_bitmap() {
+        let package_signers =
+            [SIGNERS[2], SIGNERS[0], SIGNERS[2], [0xff; 20]];
+        assert_eq!(signer_bitmap(&package_signers, &SIGNERS), 0b101);
+        assert_eq!(signer_bitmap(&package_signers, &SIGNERS[1..]), 0b10);
+        assert_eq!(signer_bitmap(&[], &SIGNERS), 0);
+
+        let signers: Vec<SignerAddress> =
+            (0..MAX_SIGNERS as u8).map(|i| [i; 20]).collect();
+        assert_eq!(signer_bitmap(&signers, &signers), u32::MAX);
+    }
+
+    #[test]
+    fn test_package_timestamp() {
+        let mut config = Config {
+            block_timestamp: 0,
+            signers: &SIGNERS,
+            signer_count_threshold: 3,
+            max_timestamp_delay_ms: 0,
+            max_timestamp_ahead_ms: 0,
+            max_package_timestamp_skew_ms: 0,
+            timestamp_aggregation: TimestampAggregation::Min,
+        };
+        let packages = |timestamps: &[u64]| -> Vec<DataPackage> {
+            timestamps
+                .iter()
+                .map(|&timestamp| DataPackage {
+                    signer_address: SIGNERS[0],
+                    timestamp,
+                    data_points: vec![],
+                })
+                .collect()
+        };
+
+        let same = packages(&[1_000, 1_000, 1_000]);
+        assert_eq!(package_timestamp(&same, &config).unwrap(), 1_000);
+
+        let skewed = packages(&[3_000, 1_000, 2_000]);
+        assert_eq!(
+            package_timestamp(&skewed, &config).unwrap_err(),
+            RedstoneError::TimestampMismatch.into()
+        );
+
+        config.max_package_timestamp_skew_ms = 2_000;
+        assert_eq!(package_timestamp(&skewed, &config).unwrap(), 1_000);
+        config.timestamp_aggregation = TimestampAggregation::Median;
+        assert_eq!(package_timestamp(&skewed, &config).unwrap(), 2_000);
+
+        config.max_package_timestamp_skew_ms = 1_999;
+        assert!(package_timestamp(&skewed, &config).is_err());
+        assert!(package_timestamp(&[], &config).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_feed() {
+        let config = Config {
+            block_timestamp: 0,
+            signers: &SIGNERS,
+            signer_count_threshold: 3,
+            max_timestamp_delay_ms: 0,
+            max_timestamp_ahead_ms: 0,
+            max_package_timestamp_skew_ms: 0,
+            timestamp_aggregation: TimestampAggregation::Min,
+        };
+        let signed_values: Vec<(SignerAddress, U256)> = SIGNERS
+            .iter()
+            .zip([100u64, 104, 98, 150])
+            .map(|(signer, value)| (*signer, U256::from(value)))
+            .collect();
+
+        let aggregated =
+            aggregate_feed(signed_values.clone(), None, &config).unwrap();
+        assert_eq!(aggregated.value, U256::from(102));
+        assert_eq!(aggregated.signer_bitmap, 0b1111);
+        assert_eq!(aggregated.lowest_value, U256::from(98));
+        assert_eq!(aggregated.highest_value, U256::from(150));
+
+        let feed_config = FeedConfig {
+            aggregation: Some(Aggregation::Max),
+            outlier_filter: Some(OutlierFilter::Band {
+                max_deviation_bps: 1_000,
+            }),
+            ..Default::default()
+        };
+        let aggregated = aggregate_feed(
+            signed_values.clone(),
+            Some(&feed_config),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(aggregated.value, U256::from(104));
+        assert_eq!(aggregated.signer_bitmap, 0b0111);
+        assert_eq!(aggregated.highest_value, U256::from(104));
+
+        let feed_config = FeedConfig {
+            max_value: Some(U256::from(101).to_bytes_be()),
+            ..Default::default()
+        };
+        assert!(aggregate_feed(signed_values, Some(&feed_config), &config)
+            .is_err());
+    }
+
+    #[test]
+    fn test_unknown_and_duplicate_signers_are_ignored() {
+        let feed_id: FeedId = "BTC".parse().unwrap();
+        let package =
+            |signer_address: SignerAddress, value: u64| DataPackage {
+                signer_address,
+                timestamp: 1_000,
+                data_points: vec![DataPoint {
+                    feed_id,
+                    value: U256::from(value).to_bytes_be(),
+                }],
+            };
+        let data_packages = vec![
+            package(SIGNERS[0], 100),
+            package([0xff; 20], 1_000_000),
+            package(SIGNERS[1], 101),
+            package(SIGNERS[0], 1_000_000),
+            package(SIGNERS[2], 102),
+        ];
+        let config = Config {
+            block_timestamp: 0,
+            signers: &SIGNERS,
+            signer_count_threshold: 3,
+            max_timestamp_delay_ms: 0,
+            max_timestamp_ahead_ms: 0,
+            max_package_timestamp_skew_ms: 0,
+            timestamp_aggregation: TimestampAggregation::Min,
+        };
+        // duplicates of one signer do not make up the threshold
+        assert!(verify_signer_count(
+            &[package(SIGNERS[0], 100), package(SIGNERS[0], 100)],
+            2,
+            &SIGNERS
+        )
+        .is_err());
+
+        let data_packages = configured_packages(data_packages, &SIGNERS);
+        assert_eq!(data_packages.len(), 4);
+        assert_eq!(feed_packages(&data_packages, &feed_id).len(), 3);
+        let feed_config = FeedConfig {
+            aggregation: Some(Aggregation::Max),
+            ..Default::default()
+        };
+        let aggregated = aggregate_feed(
+            signed_values(&data_packages, &feed_id),
+            Some(&feed_config),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(aggregated.value, U256::from(102));
+        assert_eq!(aggregated.signer_bitmap, 0b111);
+    }
+
+    #[test]
+    fn test_filter_outliers() {
+        let config = Config {
+            block_timestamp: 0,
+            signers: &SIGNERS,
+            signer_count_threshold: 3,
+            max_timestamp_delay_ms: 0,
+            max_timestamp_ahead_ms: 0,
+            max_package_timestamp_skew_ms: 0,
+            timestamp_aggregation: TimestampAggregation::Min,
+        };
+        let signed_values = |values: &[u64]| -> Vec<(SignerAddress, U256)> {
+            SIGNERS
+                .iter()
+                .zip(values)
+                .map(|(signer, &value)| (*signer, U256::from(value)))
+                .collect()
+        };
+        let filter = OutlierFilter::Band {
+            max_deviation_bps: 500,
+        };
+
+        let kept = filter_outliers(
+            signed_values(&[100, 101, 99, 150]),
+            &filter,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(kept, signed_values(&[100, 101, 99]));
+
+        let res = filter_outliers(
+            signed_values(&[100, 101, 150, 50]),
+            &filter,
+            &config,
+        );
+        assert_eq!(
+            res.unwrap_err(),
+            RedstoneError::InsufficientSignerCount.into()
+        );
+    }
+
+    #[test]
+    fn test_verify_update_interval() {
+        let mut price_data = PriceData::zeroed();
+        let value = U256::from(10_000u64);
+        let feed_config = FeedConfig {
+            min_update_interval_ms: Some(60_000),
+            update_deviation_bps: Some(100),
+            ..Default::default()
+        };
+        let verify = |price_data: &PriceData, value: u64, now: u64| {
+            verify_update_interval(
+                price_data,
+                &U256::from(value),
+                now,
+                &feed_config,
+            )
+        };
+
+        // first write
+        assert!(verify(&price_data, 10_000, 1_000).is_ok());
+
+        price_data.value = value.to_bytes_be();
+        price_data.write_timestamp = 1_000_000;
+        assert_eq!(
+            verify(&price_data, 10_099, 1_059_999).unwrap_err(),
+            RedstoneError::UpdateTooFrequent.into()
+        );
+        assert!(verify(&price_data, 10_000, 1_060_000).is_ok());
+        assert!(verify(&price_data, 10_100, 1_000_000).is_ok());
+        assert!(verify(&price_data, 9_900, 1_000_000).is_ok());
+
+        let no_exemption = FeedConfig {
+            update_deviation_bps: None,
+            ..feed_config.clone()
+        };
+        assert!(verify_update_interval(
+            &price_data,
+            &U256::from(20_000u64),
+            1_000_000,
+            &no_exemption
+        )
+        .is_err());
+        assert!(verify_update_interval(
+            &price_data,
+            &value,
+            1_000_000,
+            &FeedConfig::default()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_verify_value_bounds() {
+        let feed_config = FeedConfig {
+            min_value: Some(U256::from(10u64).to_bytes_be()),
+            max_value: Some(U256::from(20u64).to_bytes_be()),
+            ..Default::default()
+        };
+        assert!(verify_value_bounds(&U256::from(10u64), &feed_config).is_ok());
+        assert!(verify_value_bounds(&U256::from(20u64), &feed_config).is_ok());
+        assert!(verify_value_bounds(&U256::from(9u64), &feed_config).is_err());
+        assert!(verify_value_bounds(&U256::MAX, &feed_config).is_err());
+        assert!(
+            verify_value_bounds(&U256::MAX, &FeedConfig::default()).is_ok()
+        );
+    }
 }
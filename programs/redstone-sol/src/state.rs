@@ -1,16 +1,120 @@
 use anchor_lang::prelude::*;
 
+pub use crate::aggregation::{
+    Aggregation, OutlierFilter, TimestampAggregation,
+};
+pub use crate::feed_id::FeedId;
+
 pub type SignerAddress = [u8; 20];
-pub type FeedId = [u8; 32];
 pub type Value = [u8; 32];
 
-#[account]
-#[derive(Default)]
+/// Zero-copy price account, readable without deserialization.
+///
+/// The layout is stable, offsets from the start of the account data
+/// (after the 8-byte discriminator):
+///
+/// | offset | size | field               |
+/// |--------|------|---------------------|
+/// | 8      | 32   | `feed_id`           |
+/// | 40     | 32   | `value` (big-endian)|
+/// | 72     | 8    | `timestamp`         |
+/// | 80     | 8    | `write_timestamp`   |
+/// | 88     | 32   | `pending_value`     |
+/// | 120    | 8    | `pending_timestamp` |
+/// | 128    | 2    | `max_jump_bps`      |
+/// | 130    | 1    | `version`           |
+/// | 131    | 1    | `decimals`          |
+/// | 132    | 4    | `signer_bitmap`     |
+/// | 136    | 1    | `signer_count`      |
+/// | 144    | 32   | `lowest_value`      |
+/// | 176    | 32   | `highest_value`     |
+/// | 208    | 8    | `write_slot`        |
+/// | 216    | 8    | `round_id`          |
+///
+/// Integers are little-endian. The first 88 bytes match the layout of
+/// the accounts created before versioning.
+#[account(zero_copy)]
+#[repr(C)]
 pub struct PriceData {
     pub feed_id: FeedId,
     pub value: Value,
     pub timestamp: u64,
     pub write_timestamp: u64,
+    /// Update held back by the circuit breaker, awaiting owner approval
+    pub pending_value: Value,
+    pub pending_timestamp: u64,
+    /// Maximum allowed move against the stored value, in basis points;
+    /// zero disables the circuit breaker
+    pub max_jump_bps: u16,
+    /// Layout version, zero for accounts created before versioning
+    pub version: u8,
+    /// Number of decimals of `value` and `pending_value`
+    pub decimals: u8,
+    /// Configured signers backing `value`, bit `i` stands for the signer
+    /// at index `i` of `ConfigAccount::signers` at the time of the update
+    pub signer_bitmap: u32,
+    /// Number of configured signers backing `value`, zero for values
+    /// accepted by the owner or written before it was tracked
+    pub signer_count: u8,
+    pub _reserved: [u8; 7],
+    /// Spread of the signed values `value` was aggregated from
+    pub lowest_value: Value,
+    pub highest_value: Value,
+    /// Slot of the update
+    pub write_slot: u64,
+    /// Incremented with every write of `value`, zero before the first one
+    pub round_id: u64,
+}
+
+/// Chainlink `latestRoundData` view of a `PriceData` account, timestamps
+/// are in milliseconds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RoundData {
+    pub round_id: u64,
+    /// Big-endian value with `PriceData::decimals` decimals
+    pub answer: Value,
+    /// Package timestamp
+    pub started_at: u64,
+    /// Write timestamp
+    pub updated_at: u64,
+    /// Always `round_id`, rounds are answered when written
+    pub answered_in_round: u64,
+}
+
+impl From<&PriceData> for RoundData {
+    fn from(price_data: &PriceData) -> Self {
+        RoundData {
+            round_id: price_data.round_id,
+            answer: price_data.value,
+            started_at: price_data.timestamp,
+            updated_at: price_data.write_timestamp,
+            answered_in_round: price_data.round_id,
+        }
+    }
+}
+
+/// Entry of the `get_prices` result
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PriceEntry {
+    pub feed_id: FeedId,
+    /// Big-endian value, zero unless the price is available
+    pub value: Value,
+    /// Package timestamp, ms
+    pub timestamp: u64,
+    pub status: PriceStatus,
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum PriceStatus {
+    Ok,
+    /// Older than the requested max age, the value is still returned
+    Stale,
+    /// The price account was not created
+    Missing,
+    /// The price account awaits `migrate_price_account`
+    Outdated,
 }
 
 #[account]
@@ -20,6 +124,192 @@ pub struct ConfigAccount {
     pub signers: Vec<SignerAddress>,
     pub max_timestamp_delay_ms: u64,
     pub max_timestamp_ahead_ms: u64,
+    /// Layout version, meaningless in configs created before versioning,
+    /// which are told apart by their size
+    pub version: u8,
+}
+
+impl ConfigAccount {
+    pub fn space(signer_count: usize) -> usize {
+        8 + 32 + 1 + 4 + signer_count * 20 + 8 + 8 + 1
+    }
+}
+
+/// Per-feed overrides of `ConfigAccount`, `None` falls back to the global
+/// value
+#[account]
+#[derive(Default)]
+pub struct FeedConfig {
+    pub feed_id: FeedId,
+    pub signer_count_threshold: Option<u8>,
+    pub max_timestamp_delay_ms: Option<u64>,
+    pub max_timestamp_ahead_ms: Option<u64>,
+    pub decimals: Option<u8>,
+    pub min_value: Option<Value>,
+    pub max_value: Option<Value>,
+    /// Median unless set
+    pub aggregation: Option<Aggregation>,
+    /// Applied before the aggregation, disabled unless set
+    pub outlier_filter: Option<OutlierFilter>,
+    /// Allowed spread of the package timestamps of a payload, zero unless
+    /// set
+    pub max_package_timestamp_skew_ms: Option<u64>,
+    /// Oldest package timestamp unless set
+    pub timestamp_aggregation: Option<TimestampAggregation>,
+    /// Makes updates not newer than the stored price succeed without
+    /// changes instead of failing, for redundant pushers; off unless set
+    pub skip_if_not_newer: Option<bool>,
+    /// Minimum time between writes, measured from `PriceData::write_timestamp`
+    pub min_update_interval_ms: Option<u64>,
+    /// Deviation from the stored value exempting an update from
+    /// `min_update_interval_ms`, no exemption unless set
+    pub update_deviation_bps: Option<u64>,
+}
+
+impl FeedConfig {
+    pub fn space() -> usize {
+        8 + std::mem::size_of::<FeedConfig>()
+    }
+
+    /// Feed configs are optional, an uninitialized PDA yields `None`.
+    /// Fields missing from configs created by older versions read as `None`
+    pub fn load(account: &AccountInfo) -> Result<Option<Self>> {
+        if account.owner != &crate::ID || account.data_is_empty() {
+            return Ok(None);
+        }
+        let mut data = account.try_borrow_data()?.to_vec();
+        data.resize(data.len().max(Self::space()), 0);
+        Ok(Some(FeedConfig::try_deserialize(&mut &data[..])?))
+    }
+}
+
+/// List of the supported feeds, price accounts can only be created and
+/// updated for the active ones
+#[account]
+#[derive(Default)]
+pub struct FeedRegistry {
+    pub feeds: Vec<RegisteredFeed>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct RegisteredFeed {
+    pub feed_id: FeedId,
+    pub active: bool,
+}
+
+impl FeedRegistry {
+    pub fn space(feed_count: usize) -> usize {
+        8 + 4 + feed_count * std::mem::size_of::<RegisteredFeed>()
+    }
+
+    pub fn find_mut(
+        &mut self,
+        feed_id: &FeedId,
+    ) -> Option<&mut RegisteredFeed> {
+        self.feeds.iter_mut().find(|feed| &feed.feed_id == feed_id)
+    }
+
+    pub fn contains(&self, feed_id: &FeedId) -> bool {
+        self.feeds.iter().any(|feed| &feed.feed_id == feed_id)
+    }
+
+    pub fn is_active(&self, feed_id: &FeedId) -> bool {
+        self.feeds
+            .iter()
+            .any(|feed| &feed.feed_id == feed_id && feed.active)
+    }
+}
+
+/// Prices of a group of feeds written together from a single payload, all
+/// sharing its timestamp
+#[account]
+#[derive(Debug)]
+pub struct PriceSnapshot {
+    pub group_id: FeedId,
+    /// Package timestamp, ms
+    pub timestamp: u64,
+    /// Block timestamp of the update, ms
+    pub write_timestamp: u64,
+    pub write_slot: u64,
+    /// Incremented with every update, zero before the first one
+    pub round_id: u64,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub feed_id: FeedId,
+    /// Big-endian value, zero before the first update
+    pub value: Value,
+    pub decimals: u8,
+    pub signer_count: u8,
+}
+
+impl PriceSnapshot {
+    pub fn space(feed_count: usize) -> usize {
+        8 + 32 + 8 + 8 + 8 + 8 + 4 + feed_count * (32 + 32 + 1 + 1)
+    }
+
+    pub fn entry(&self, feed_id: &FeedId) -> Option<&SnapshotEntry> {
+        self.entries.iter().find(|entry| &entry.feed_id == feed_id)
+    }
+}
+
+/// Cross rate of two registered feeds, `numerator / denominator`, stored
+/// in a price account of its own and recomputed whenever either source is
+/// updated through `process_redstone_payload`
+#[account]
+pub struct DerivedFeed {
+    pub feed_id: FeedId,
+    pub numerator: FeedId,
+    pub denominator: FeedId,
+    /// Decimals of the derived value
+    pub decimals: u8,
+}
+
+impl DerivedFeed {
+    pub fn space() -> usize {
+        8 + 32 + 32 + 32 + 1
+    }
+
+    /// The other source of the derived feed, `None` unless `feed_id` is
+    /// one of them
+    pub fn other_source(&self, feed_id: &FeedId) -> Option<FeedId> {
+        if feed_id == &self.numerator {
+            Some(self.denominator)
+        } else if feed_id == &self.denominator {
+            Some(self.numerator)
+        } else {
+            None
+        }
+    }
+}
+
+/// Weighted sum of registered feeds, e.g. an LP token or a stablecoin
+/// basket, stored in a price account of its own and recomputed by
+/// `update_basket_feed`
+#[account]
+pub struct BasketFeed {
+    pub feed_id: FeedId,
+    /// Decimals of the basket value
+    pub decimals: u8,
+    /// Decimals of the component weights
+    pub weight_decimals: u8,
+    /// Maximum age of every component price at the update
+    pub max_component_age_ms: u64,
+    pub components: Vec<BasketComponent>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BasketComponent {
+    pub feed_id: FeedId,
+    pub weight: u64,
+}
+
+impl BasketFeed {
+    pub fn space(component_count: usize) -> usize {
+        8 + 32 + 1 + 1 + 8 + 4 + component_count * (32 + 8)
+    }
 }
 
 pub struct DataPoint {
@@ -39,5 +329,39 @@ pub struct Payload {
 
 pub struct Config<'a> {
     pub block_timestamp: u64,
-    pub config_account: &'a ConfigAccount,
+    pub signers: &'a [SignerAddress],
+    pub signer_count_threshold: u8,
+    pub max_timestamp_delay_ms: u64,
+    pub max_timestamp_ahead_ms: u64,
+    pub max_package_timestamp_skew_ms: u64,
+    pub timestamp_aggregation: TimestampAggregation,
+}
+
+impl<'a> Config<'a> {
+    pub fn new(
+        block_timestamp: u64,
+        config_account: &'a ConfigAccount,
+        feed_config: Option<&FeedConfig>,
+    ) -> Self {
+        let feed_config = feed_config.cloned().unwrap_or_default();
+        Config {
+            block_timestamp,
+            signers: &config_account.signers,
+            signer_count_threshold: feed_config
+                .signer_count_threshold
+                .unwrap_or(config_account.signer_count_threshold),
+            max_timestamp_delay_ms: feed_config
+                .max_timestamp_delay_ms
+                .unwrap_or(config_account.max_timestamp_delay_ms),
+            max_timestamp_ahead_ms: feed_config
+                .max_timestamp_ahead_ms
+                .unwrap_or(config_account.max_timestamp_ahead_ms),
+            max_package_timestamp_skew_ms: feed_config
+                .max_package_timestamp_skew_ms
+                .unwrap_or_default(),
+            timestamp_aggregation: feed_config
+                .timestamp_aggregation
+                .unwrap_or_default(),
+        }
+    }
 }
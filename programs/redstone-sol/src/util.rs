@@ -1,5 +1,7 @@
 use zkp_u256::U256;
 
+use crate::constants::BPS_DENOMINATOR;
+
 pub trait Trim<T>
 where
     Self: Sized,
@@ -17,22 +19,43 @@ impl Trim<Vec<u8>> for Vec<u8> {
     }
 }
 
-pub fn u256_to_string(u256: &[u8; 32]) -> String {
-    u256.iter()
-        .take_while(|&&c| c != 0)
-        .map(|&c| c as char)
-        .collect()
+/// Full-width decimal representation of a big-endian 256-bit number
+pub fn u256_to_num_string(u256: &[u8; 32]) -> String {
+    u256_to_decimal_string(&U256::from_bytes_be(u256))
 }
 
-pub fn u256_to_num_string(u256: &[u8; 32]) -> String {
-    let mut num = 0u128;
-    for &c in u256 {
-        num = (num << 8) | c as u128;
+pub fn u256_to_decimal_string(value: &U256) -> String {
+    // largest power of ten fitting in u64, the number is split into
+    // 19-digit chunks instead of being divided digit by digit
+    const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+    let mut chunks = Vec::with_capacity(4);
+    let mut value = value.clone();
+    loop {
+        let (quotient, remainder) = value.divrem_u64(CHUNK).unwrap();
+        chunks.push(remainder);
+        if quotient.is_zero() {
+            break;
+        }
+        value = quotient;
+    }
+
+    let mut result = chunks.pop().unwrap().to_string();
+    for chunk in chunks.iter().rev() {
+        result.push_str(&format!("{:019}", chunk));
+    }
+    result
+}
+
+/// Minimal `0x`-prefixed hex representation of a big-endian 256-bit number
+pub fn u256_to_hex_string(u256: &[u8; 32]) -> String {
+    let hex: String = u256.iter().map(|b| format!("{:02x}", b)).collect();
+    match hex.trim_start_matches('0') {
+        "" => "0x0".to_string(),
+        digits => format!("0x{}", digits),
     }
-    num.to_string()
 }
 
-#[cfg(feature = "dev")]
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
     use std::fmt::Write;
     bytes.iter().fold(
@@ -99,6 +122,31 @@ pub fn avg_u256(a: &U256, b: &U256) -> U256 {
     }
 }
 
+/// Relative difference between `previous` and `current` in basis points,
+/// saturating at `u64::MAX`; `None` if `previous` is zero
+pub fn deviation_bps(previous: &U256, current: &U256) -> Option<u64> {
+    if previous.is_zero() {
+        return None;
+    }
+    let diff = if current > previous {
+        current - previous
+    } else {
+        previous - current
+    };
+    let (quotient, remainder) = diff.divrem(previous)?;
+    if quotient > U256::from(u64::MAX / BPS_DENOMINATOR - 1) {
+        return Some(u64::MAX);
+    }
+    // remainder < previous, so scaling both down keeps the fraction
+    // below one while avoiding the overflow of remainder * 10^4
+    let fraction = if remainder.bits() + 14 <= 256 {
+        remainder * BPS_DENOMINATOR / previous
+    } else {
+        (remainder >> 14) * BPS_DENOMINATOR / (previous.clone() >> 14)
+    };
+    Some(quotient.as_u64() * BPS_DENOMINATOR + fraction.as_u64())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,6 +175,83 @@ mod tests {
         assert_eq!(result.unwrap(), U256::MAX - U256::from(5));
     }
 
+    #[test]
+    fn test_deviation_bps() {
+        let previous = U256::from(100_000_000u64);
+        assert_eq!(deviation_bps(&previous, &previous), Some(0));
+        assert_eq!(
+            deviation_bps(&previous, &U256::from(110_000_000u64)),
+            Some(1_000)
+        );
+        assert_eq!(
+            deviation_bps(&previous, &U256::from(10_000_000u64)),
+            Some(9_000)
+        );
+        assert_eq!(deviation_bps(&U256::ZERO, &previous), None);
+    }
+
+    /// checks if no overflow occurs
+    #[test]
+    fn test_deviation_bps_with_max_values() {
+        assert_eq!(deviation_bps(&U256::ONE, &U256::MAX), Some(u64::MAX));
+        assert_eq!(deviation_bps(&U256::MAX, &(U256::MAX >> 1)), Some(5_000));
+        assert_eq!(deviation_bps(&U256::MAX, &U256::ZERO), Some(10_000));
+    }
+
+    #[test]
+    fn test_u256_to_num_string() {
+        assert_eq!(u256_to_num_string(&[0; 32]), "0");
+        assert_eq!(
+            u256_to_num_string(&U256::from(123_456_789u64).to_bytes_be()),
+            "123456789"
+        );
+        assert_eq!(
+            u256_to_num_string(&U256::from(u128::MAX).to_bytes_be()),
+            u128::MAX.to_string()
+        );
+        // u128::MAX + 1
+        assert_eq!(
+            u256_to_num_string(
+                &(U256::from(u128::MAX) + U256::ONE).to_bytes_be()
+            ),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(
+            u256_to_num_string(&U256::MAX.to_bytes_be()),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        // chunks with leading zeros
+        assert_eq!(
+            u256_to_num_string(
+                &U256::from_decimal_str(
+                    "100000000000000000000000000000000000001"
+                )
+                .unwrap()
+                .to_bytes_be()
+            ),
+            "100000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_u256_to_hex_string() {
+        assert_eq!(u256_to_hex_string(&[0; 32]), "0x0");
+        assert_eq!(
+            u256_to_hex_string(&U256::from(0x1234u64).to_bytes_be()),
+            "0x1234"
+        );
+        assert_eq!(
+            u256_to_hex_string(
+                &(U256::from(u128::MAX) + U256::ONE).to_bytes_be()
+            ),
+            "0x100000000000000000000000000000000"
+        );
+        assert_eq!(
+            u256_to_hex_string(&U256::MAX.to_bytes_be()),
+            format!("0x{}", "f".repeat(64))
+        );
+    }
+
     /// median of u128 implementation for benchmark comparison
     /// RNG fuzz benchmark results:
     /// Total iterations: 1000
@@ -0,0 +1,230 @@
+//! Weighted sums of registered feeds.
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::signature::Signer;
+
+use common::*;
+use redstone_sol::consumer::price_account_address;
+use redstone_sol::error::RedstoneError;
+use redstone_sol::state::{BasketComponent, FeedId};
+
+const MAX_AGE_MS: u64 = 60_000;
+
+/// Defines `feed_id` as 0.5 of the first feed and 2 of the second one
+fn set_basket_feed_instruction(
+    owner: Pubkey,
+    feed_id: FeedId,
+    feed_ids: [FeedId; 2],
+) -> Instruction {
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::SetBasketFeed {
+            owner,
+            config_account: pda(&[b"config"]),
+            feed_registry: pda(&[b"registry"]),
+            basket_feed: pda(&[b"basket_feed", feed_id.as_ref()]),
+            price_account: price_account_address(&feed_id),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::SetBasketFeed {
+            feed_id,
+            components: vec![
+                BasketComponent {
+                    feed_id: feed_ids[0],
+                    weight: 50,
+                },
+                BasketComponent {
+                    feed_id: feed_ids[1],
+                    weight: 200,
+                },
+            ],
+            weight_decimals: 2,
+            decimals: 8,
+            max_component_age_ms: MAX_AGE_MS,
+        }
+        .data(),
+    }
+}
+
+fn update_basket_feed_instruction(
+    feed_id: FeedId,
+    feed_ids: [FeedId; 2],
+) -> Instruction {
+    let mut accounts = redstone_sol::accounts::UpdateBasketFeed {
+        basket_feed: pda(&[b"basket_feed", feed_id.as_ref()]),
+        price_account: price_account_address(&feed_id),
+    }
+    .to_account_metas(None);
+    for feed_id in &feed_ids {
+        accounts.push(AccountMeta::new_readonly(
+            price_account_address(feed_id),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts,
+        data: redstone_sol::instruction::UpdateBasketFeed { feed_id }.data(),
+    }
+}
+
+#[tokio::test]
+async fn test_basket_feed() {
+    let (mut context, package_timestamp) = setup().await;
+    let owner = context.payer.pubkey();
+    let btc = btc_feed_id();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let basket: FeedId = "BASKET".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    set_price(
+        &mut context,
+        &eth,
+        250_000_000_000,
+        package_timestamp - 1000,
+    );
+    send(
+        &mut context,
+        set_basket_feed_instruction(owner, basket, [btc, eth]),
+    )
+    .await
+    .unwrap();
+    send(
+        &mut context,
+        update_basket_feed_instruction(basket, [btc, eth]),
+    )
+    .await
+    .unwrap();
+
+    let btc_price = price_data(&mut context, &btc).await;
+    let btc_price =
+        u128::from_be_bytes(btc_price.value[16..].try_into().unwrap());
+    let price = price_data(&mut context, &basket).await;
+    assert_eq!(
+        u128::from_be_bytes(price.value[16..].try_into().unwrap()),
+        btc_price / 2 + 2 * 250_000_000_000
+    );
+    assert_eq!(price.timestamp, package_timestamp - 1000);
+    assert_eq!(price.signer_count, 3);
+    assert_eq!(price.round_id, 1);
+
+    // the ETH price is the first one to go stale
+    next_slot(&mut context).await;
+    set_clock_ms(&mut context, package_timestamp + MAX_AGE_MS).await;
+    let err = send(
+        &mut context,
+        update_basket_feed_instruction(basket, [btc, eth]),
+    )
+    .await
+    .unwrap_err();
+    assert_error(err, RedstoneError::StalePrice);
+}
+
+#[tokio::test]
+async fn test_basket_round_id_moves_only_on_change() {
+    let (mut context, package_timestamp) = start().await;
+    let owner = context.payer.pubkey();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let components = [btc_feed_id(), eth];
+    let basket: FeedId = "BASKET".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    set_price(
+        &mut context,
+        &components[0],
+        6_000_000_000_000,
+        package_timestamp,
+    );
+    set_price(&mut context, &eth, 250_000_000_000, package_timestamp);
+    send(
+        &mut context,
+        set_basket_feed_instruction(owner, basket, components),
+    )
+    .await
+    .unwrap();
+
+    send(
+        &mut context,
+        update_basket_feed_instruction(basket, components),
+    )
+    .await
+    .unwrap();
+    let price = price_data(&mut context, &basket).await;
+    assert_eq!(price.round_id, 1);
+
+    next_slot(&mut context).await;
+    send(
+        &mut context,
+        update_basket_feed_instruction(basket, components),
+    )
+    .await
+    .unwrap();
+    let unchanged = price_data(&mut context, &basket).await;
+    assert_eq!(unchanged.round_id, 1);
+    assert_eq!(unchanged.write_slot, price.write_slot);
+
+    set_price(&mut context, &eth, 260_000_000_000, package_timestamp);
+    next_slot(&mut context).await;
+    send(
+        &mut context,
+        update_basket_feed_instruction(basket, components),
+    )
+    .await
+    .unwrap();
+    let changed = price_data(&mut context, &basket).await;
+    assert_eq!(changed.round_id, 2);
+    assert_ne!(changed.value, price.value);
+}
+
+#[tokio::test]
+async fn test_set_basket_feed_rejects_derived_feed_id() {
+    let (mut context, _) = start().await;
+    let owner = context.payer.pubkey();
+    let btc = btc_feed_id();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let eth_btc: FeedId = "ETH/BTC".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    send(
+        &mut context,
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::CreateDerivedFeed {
+                owner,
+                config_account: pda(&[b"config"]),
+                feed_registry: pda(&[b"registry"]),
+                derived_feed: pda(&[b"derived_feed", eth_btc.as_ref()]),
+                price_account: price_account_address(&eth_btc),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::CreateDerivedFeed {
+                feed_id: eth_btc,
+                numerator: eth,
+                denominator: btc,
+                decimals: 10,
+            }
+            .data(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut context,
+        set_basket_feed_instruction(owner, eth_btc, [btc, eth]),
+    )
+    .await
+    .unwrap_err();
+    assert_error(err, RedstoneError::FeedIdInUse);
+}
@@ -0,0 +1,114 @@
+//! Holds back price jumps above the feed's limit until the owner accepts
+//! them.
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
+use bytemuck::Zeroable;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+use common::*;
+use redstone_sol::constants::PRICE_DATA_VERSION;
+use redstone_sol::consumer::price_account_address;
+use redstone_sol::state::{PriceData, Value};
+
+fn accept_pending_price_instruction(owner: Pubkey) -> Instruction {
+    let feed_id = btc_feed_id();
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::AcceptPendingPrice {
+            owner,
+            config_account: pda(&[b"config"]),
+            price_account: price_account_address(&feed_id),
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::AcceptPendingPrice { feed_id }
+            .data(),
+    }
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_holds_jump_until_accepted() {
+    let (mut context, package_timestamp) = start().await;
+    let owner = context.payer.pubkey();
+    let feed_id = btc_feed_id();
+
+    // a stored BTC price of 10000, far below the payload
+    let mut stored = PriceData::zeroed();
+    stored.feed_id = feed_id;
+    stored.value[24..].copy_from_slice(&1_000_000_000_000u64.to_be_bytes());
+    stored.timestamp = package_timestamp - 60_000;
+    stored.version = PRICE_DATA_VERSION;
+    stored.decimals = 8;
+    stored.round_id = 1;
+    let mut data = PriceData::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&stored));
+    set_program_account(
+        &mut context,
+        &price_account_address(&feed_id),
+        &data,
+    );
+
+    send(
+        &mut context,
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::SetMaxJumpBps {
+                owner,
+                config_account: pda(&[b"config"]),
+                price_account: price_account_address(&feed_id),
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::SetMaxJumpBps {
+                feed_id,
+                max_jump_bps: 1_000,
+            }
+            .data(),
+        },
+    )
+    .await
+    .unwrap();
+
+    send(&mut context, process_payload_instruction(owner, vec![]))
+        .await
+        .unwrap();
+    let held = price_data(&mut context, &feed_id).await;
+    assert_eq!(held.value, stored.value);
+    assert_eq!(held.timestamp, stored.timestamp);
+    assert_eq!(held.round_id, 1);
+    assert_ne!(held.pending_value, Value::default());
+    assert_eq!(held.pending_timestamp, package_timestamp);
+
+    // only the owner can accept it
+    let stranger = Keypair::new();
+    let blockhash =
+        context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[accept_pending_price_instruction(stranger.pubkey())],
+        Some(&owner),
+        &[&context.payer, &stranger],
+        blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains(&format!(
+        "{:#x}",
+        u32::from(anchor_lang::error::ErrorCode::ConstraintHasOne)
+    )));
+    assert_eq!(price_data(&mut context, &feed_id).await.value, stored.value);
+
+    send(&mut context, accept_pending_price_instruction(owner))
+        .await
+        .unwrap();
+    let accepted = price_data(&mut context, &feed_id).await;
+    assert_eq!(accepted.value, held.pending_value);
+    assert_eq!(accepted.timestamp, package_timestamp);
+    assert_eq!(accepted.round_id, 2);
+    assert_eq!(accepted.pending_value, Value::default());
+    assert_eq!(accepted.pending_timestamp, 0);
+}
@@ -0,0 +1,356 @@
+//! Program-test harness shared by the integration tests
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
+use bytemuck::Zeroable;
+use libsecp256k1::{Message, PublicKey, SecretKey};
+use solana_program_test::{
+    processor, BanksClientError, ProgramTest, ProgramTestContext,
+};
+use solana_sdk::account::AccountSharedData;
+use solana_sdk::keccak;
+use solana_sdk::{signature::Signer, transaction::Transaction};
+
+use redstone_sol::constants::REDSTONE_MARKER;
+use redstone_sol::consumer::price_account_address;
+use redstone_sol::error::RedstoneError;
+use redstone_sol::state::{FeedId, PriceData, SignerAddress};
+
+/// Signed BTC payload with three data packages
+pub const PAYLOAD: &[u8] = &[
+    66, 84, 67, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 58, 182, 116, 104, 177, 1, 146, 170,
+    223, 139, 176, 0, 0, 0, 32, 0, 0, 1, 43, 218, 184, 99, 113, 194, 156,
+    200, 231, 35, 84, 134, 87, 215, 8, 159, 159, 138, 105, 210, 213, 205,
+    124, 73, 234, 227, 40, 9, 226, 13, 146, 163, 92, 252, 93, 106, 169, 6,
+    115, 168, 226, 166, 112, 111, 90, 237, 27, 253, 190, 225, 47, 16, 208,
+    114, 14, 12, 235, 44, 110, 244, 188, 96, 6, 91, 28, 66, 84, 67, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 6, 58, 182, 116, 104, 177, 1, 146, 170, 223, 139, 176, 0, 0,
+    0, 32, 0, 0, 1, 143, 215, 175, 236, 103, 162, 86, 18, 42, 103, 87, 163,
+    21, 179, 144, 222, 90, 247, 211, 241, 62, 242, 230, 233, 83, 191, 182,
+    114, 36, 143, 211, 94, 101, 30, 185, 70, 46, 86, 232, 98, 31, 250, 231,
+    52, 221, 240, 205, 18, 253, 240, 185, 77, 77, 238, 224, 181, 19, 123,
+    123, 165, 5, 160, 214, 87, 28, 66, 84, 67, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 58,
+    182, 131, 169, 123, 1, 146, 170, 223, 139, 176, 0, 0, 0, 32, 0, 0, 1,
+    177, 208, 87, 179, 126, 42, 24, 134, 220, 94, 31, 20, 23, 243, 240, 182,
+    150, 195, 62, 93, 178, 128, 110, 81, 78, 111, 213, 245, 165, 208, 42,
+    154, 46, 93, 3, 148, 58, 109, 254, 91, 37, 223, 99, 96, 105, 2, 113, 158,
+    31, 135, 46, 69, 135, 94, 17, 144, 2, 128, 255, 253, 252, 53, 221, 89,
+    27, 0, 3, 0, 0, 0, 0, 0, 2, 237, 87, 1, 30, 0, 0,
+];
+
+// Anchor entrypoints tie the account slice and account lifetimes together,
+// which `processor!` does not allow for
+fn redstone_sol_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    redstone_sol::entry(program_id, accounts, data)
+}
+
+pub fn btc_feed_id() -> FeedId {
+    "BTC".parse().unwrap()
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &redstone_sol::ID).0
+}
+
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+pub fn assert_error(err: BanksClientError, expected: RedstoneError) {
+    assert!(
+        err.to_string()
+            .contains(&format!("{:#x}", u32::from(expected))),
+        "expected {expected:?}, got {err}"
+    );
+}
+
+pub async fn set_clock_ms(
+    context: &mut ProgramTestContext,
+    timestamp_ms: u64,
+) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = (timestamp_ms / 1000) as i64;
+    context.set_sysvar(&clock);
+}
+
+/// Moves to the next slot, which comes with a new blockhash, so that a
+/// resent instruction is not the very same transaction
+pub async fn next_slot(context: &mut ProgramTestContext) {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.warp_to_slot(clock.slot + 1).unwrap();
+    // the new bank derives its own unix timestamp
+    let mut next_clock: Clock =
+        context.banks_client.get_sysvar().await.unwrap();
+    next_clock.unix_timestamp = clock.unix_timestamp;
+    context.set_sysvar(&next_clock);
+}
+
+/// Overwrites `address` with a rent-exempt account of the program
+pub fn set_program_account(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    data: &[u8],
+) {
+    let mut account = AccountSharedData::new(
+        Rent::default().minimum_balance(data.len()),
+        data.len(),
+        &redstone_sol::ID,
+    );
+    account.set_data_from_slice(data);
+    context.set_account(address, &account);
+}
+
+/// Sets up `redstone-sol` with the payload signers and registers BTC,
+/// returns the context and the package timestamp, the current time
+pub async fn start() -> (ProgramTestContext, u64) {
+    let payload =
+        redstone_sol::redstone::parse_raw_payload(&mut PAYLOAD.to_vec())
+            .unwrap();
+    let signers = payload
+        .data_packages
+        .iter()
+        .map(|package| package.signer_address)
+        .collect();
+    let package_timestamp = payload.data_packages[0].timestamp;
+    let context = start_with_signers(signers, package_timestamp).await;
+    (context, package_timestamp)
+}
+
+/// Sets up `redstone-sol` with `signers` at `timestamp_ms` and registers
+/// BTC
+pub async fn start_with_signers(
+    signers: Vec<SignerAddress>,
+    timestamp_ms: u64,
+) -> ProgramTestContext {
+    let program_test = ProgramTest::new(
+        "redstone_sol",
+        redstone_sol::ID,
+        processor!(redstone_sol_entry),
+    );
+    let mut context = program_test.start_with_context().await;
+    set_clock_ms(&mut context, timestamp_ms).await;
+
+    let owner = context.payer.pubkey();
+    let config_account = pda(&[b"config"]);
+    let feed_registry = pda(&[b"registry"]);
+
+    let instructions = [
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::Initialize {
+                owner,
+                config_account,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::Initialize {
+                signers,
+                signer_count_threshold: 3,
+                max_timestamp_delay_ms: 15 * 60 * 1000,
+                max_timestamp_ahead_ms: 3 * 60 * 1000,
+            }
+            .data(),
+        },
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::InitializeFeedRegistry {
+                owner,
+                config_account,
+                feed_registry,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::InitializeFeedRegistry {}.data(),
+        },
+        register_feed_instruction(owner, &btc_feed_id()),
+    ];
+    for instruction in instructions {
+        send(&mut context, instruction).await.unwrap();
+    }
+    context
+}
+
+/// Keys of three stand-in signers, for payloads the test signs itself
+pub fn signer_keys() -> Vec<SecretKey> {
+    (1..=3u8)
+        .map(|seed| SecretKey::parse(&[seed; 32]).unwrap())
+        .collect()
+}
+
+pub fn signer_address(key: &SecretKey) -> SignerAddress {
+    let public_key = PublicKey::from_secret_key(key).serialize();
+    keccak::hash(&public_key[1..]).0[12..].try_into().unwrap()
+}
+
+/// Payload of one package per key and per feed, each of them signing the
+/// same `values` with 32-byte values
+pub fn signed_payload(
+    keys: &[SecretKey],
+    timestamp: u64,
+    values: &[(FeedId, u64)],
+) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for (feed_id, value) in values {
+        for key in keys {
+            let mut package = feed_id.as_ref().to_vec();
+            package.extend_from_slice(&[0; 24]);
+            package.extend_from_slice(&value.to_be_bytes());
+            package.extend_from_slice(&timestamp.to_be_bytes()[2..]);
+            package.extend_from_slice(&32u32.to_be_bytes());
+            package.extend_from_slice(&1u32.to_be_bytes()[1..]);
+            let message = Message::parse(&keccak::hash(&package).0);
+            let (signature, recovery_id) = libsecp256k1::sign(&message, key);
+            package.extend_from_slice(&signature.serialize());
+            package.push(recovery_id.serialize() + 27);
+            payload.extend(package);
+        }
+    }
+    let package_count = (keys.len() * values.len()) as u16;
+    payload.extend_from_slice(&package_count.to_be_bytes());
+    // no unsigned metadata
+    payload.extend_from_slice(&[0; 3]);
+    payload.extend_from_slice(&REDSTONE_MARKER);
+    payload
+}
+
+pub fn register_feed_instruction(
+    owner: Pubkey,
+    feed_id: &FeedId,
+) -> Instruction {
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::RegisterFeed {
+            owner,
+            config_account: pda(&[b"config"]),
+            feed_registry: pda(&[b"registry"]),
+            derived_feed: pda(&[b"derived_feed", feed_id.as_ref()]),
+            basket_feed: pda(&[b"basket_feed", feed_id.as_ref()]),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::RegisterFeed {
+            name: feed_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+/// Pushes the BTC payload, with `accounts` as the remaining accounts
+pub fn process_payload_instruction(
+    owner: Pubkey,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let feed_id = btc_feed_id();
+    let mut metas = redstone_sol::accounts::ProcessPayload {
+        user: owner,
+        price_account: price_account_address(&feed_id),
+        config_account: pda(&[b"config"]),
+        feed_config: pda(&[b"feed_config", feed_id.as_ref()]),
+        feed_registry: pda(&[b"registry"]),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    metas.extend(accounts);
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: metas,
+        data: redstone_sol::instruction::ProcessRedstonePayload {
+            feed_id,
+            payload: PAYLOAD.to_vec(),
+        }
+        .data(),
+    }
+}
+
+pub async fn price_data(
+    context: &mut ProgramTestContext,
+    feed_id: &FeedId,
+) -> PriceData {
+    let account = context
+        .banks_client
+        .get_account(price_account_address(feed_id))
+        .await
+        .unwrap()
+        .unwrap();
+    bytemuck::pod_read_unaligned(&account.data[8..])
+}
+
+/// Writes the price account of `feed_id` directly, `value` with 8 decimals
+/// backed by 5 signers, as only a BTC payload is at hand
+pub fn set_price(
+    context: &mut ProgramTestContext,
+    feed_id: &FeedId,
+    value: u64,
+    timestamp: u64,
+) {
+    let mut price_data = PriceData::zeroed();
+    price_data.feed_id = *feed_id;
+    price_data.value[24..].copy_from_slice(&value.to_be_bytes());
+    price_data.timestamp = timestamp;
+    price_data.version = redstone_sol::constants::PRICE_DATA_VERSION;
+    price_data.decimals = 8;
+    price_data.signer_count = 5;
+    price_data.round_id = 1;
+    let mut data = PriceData::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&price_data));
+    set_program_account(context, &price_account_address(feed_id), &data);
+}
+
+/// Sets up `redstone-sol` with the payload signers and pushes the payload
+pub async fn setup() -> (ProgramTestContext, u64) {
+    let (mut context, package_timestamp) = start().await;
+    let owner = context.payer.pubkey();
+    send(&mut context, process_payload_instruction(owner, vec![]))
+        .await
+        .unwrap();
+    (context, package_timestamp)
+}
+
+/// Simulates `instruction` of `redstone-sol`, returning its return data
+pub async fn simulate_return_data(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+) -> Vec<u8> {
+    let blockhash =
+        context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(simulation.result.unwrap().is_ok());
+    let return_data =
+        simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, redstone_sol::ID);
+    return_data.data
+}
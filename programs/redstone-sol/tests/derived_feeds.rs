@@ -0,0 +1,209 @@
+//! Cross rates of two registered feeds, recomputed on source updates.
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::signature::Signer;
+
+use common::*;
+use redstone_sol::consumer::price_account_address;
+use redstone_sol::error::RedstoneError;
+use redstone_sol::state::FeedId;
+
+fn create_derived_feed_instruction(
+    owner: Pubkey,
+    feed_id: FeedId,
+    numerator: FeedId,
+    denominator: FeedId,
+) -> Instruction {
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::CreateDerivedFeed {
+            owner,
+            config_account: pda(&[b"config"]),
+            feed_registry: pda(&[b"registry"]),
+            derived_feed: pda(&[b"derived_feed", feed_id.as_ref()]),
+            price_account: price_account_address(&feed_id),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::CreateDerivedFeed {
+            feed_id,
+            numerator,
+            denominator,
+            decimals: 10,
+        }
+        .data(),
+    }
+}
+
+/// Remaining accounts recomputing `feed_id` on a BTC update
+fn derived_feed_accounts(
+    feed_id: &FeedId,
+    other: &FeedId,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(
+            pda(&[b"derived_feed", feed_id.as_ref()]),
+            false,
+        ),
+        AccountMeta::new(price_account_address(feed_id), false),
+        AccountMeta::new_readonly(price_account_address(other), false),
+    ]
+}
+
+#[tokio::test]
+async fn test_derived_feed() {
+    let (mut context, package_timestamp) = start().await;
+    let owner = context.payer.pubkey();
+    let btc = btc_feed_id();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let eth_btc: FeedId = "ETH/BTC".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    set_price(
+        &mut context,
+        &eth,
+        250_000_000_000,
+        package_timestamp - 1000,
+    );
+    send(
+        &mut context,
+        create_derived_feed_instruction(owner, eth_btc, eth, btc),
+    )
+    .await
+    .unwrap();
+
+    send(
+        &mut context,
+        process_payload_instruction(
+            owner,
+            derived_feed_accounts(&eth_btc, &eth),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let btc_price = price_data(&mut context, &btc).await;
+    let btc_price =
+        u128::from_be_bytes(btc_price.value[16..].try_into().unwrap());
+    let derived = price_data(&mut context, &eth_btc).await;
+    assert_eq!(derived.feed_id, eth_btc);
+    assert_eq!(
+        u128::from_be_bytes(derived.value[16..].try_into().unwrap()),
+        250_000_000_000u128 * 10u128.pow(10) / btc_price
+    );
+    assert_eq!(derived.decimals, 10);
+    // the older of the sources
+    assert_eq!(derived.timestamp, package_timestamp - 1000);
+    assert_eq!(derived.signer_count, 3);
+    assert_eq!(derived.round_id, 1);
+}
+
+#[tokio::test]
+async fn test_derived_feed_division_by_zero_is_skipped() {
+    let (mut context, package_timestamp) = start().await;
+    let owner = context.payer.pubkey();
+    let btc = btc_feed_id();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let btc_eth: FeedId = "BTC/ETH".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    set_price(&mut context, &eth, 0, package_timestamp - 1000);
+    send(
+        &mut context,
+        create_derived_feed_instruction(owner, btc_eth, btc, eth),
+    )
+    .await
+    .unwrap();
+
+    send(
+        &mut context,
+        process_payload_instruction(
+            owner,
+            derived_feed_accounts(&btc_eth, &eth),
+        ),
+    )
+    .await
+    .unwrap();
+
+    // the source is written, the cross rate is left as it was
+    assert_eq!(price_data(&mut context, &btc).await.round_id, 1);
+    assert_eq!(price_data(&mut context, &btc_eth).await.round_id, 0);
+}
+
+#[tokio::test]
+async fn test_register_feed_rejects_derived_feed_id() {
+    let (mut context, _) = start().await;
+    let owner = context.payer.pubkey();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let eth_btc: FeedId = "ETH/BTC".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    send(
+        &mut context,
+        create_derived_feed_instruction(owner, eth_btc, eth, btc_feed_id()),
+    )
+    .await
+    .unwrap();
+
+    let err = send(&mut context, register_feed_instruction(owner, &eth_btc))
+        .await
+        .unwrap_err();
+    assert_error(err, RedstoneError::FeedIdInUse);
+}
+
+#[tokio::test]
+async fn test_close_price_account_rejects_derived_feed_id() {
+    let (mut context, _) = start().await;
+    let owner = context.payer.pubkey();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let eth_btc: FeedId = "ETH/BTC".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    send(
+        &mut context,
+        create_derived_feed_instruction(owner, eth_btc, eth, btc_feed_id()),
+    )
+    .await
+    .unwrap();
+
+    let err = send(
+        &mut context,
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::ClosePriceAccount {
+                owner,
+                config_account: pda(&[b"config"]),
+                feed_registry: pda(&[b"registry"]),
+                price_account: price_account_address(&eth_btc),
+                derived_feed: pda(&[b"derived_feed", eth_btc.as_ref()]),
+                basket_feed: pda(&[b"basket_feed", eth_btc.as_ref()]),
+                destination: owner,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::ClosePriceAccount {
+                feed_id: eth_btc,
+            }
+            .data(),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_error(err, RedstoneError::FeedIdInUse);
+    assert!(context
+        .banks_client
+        .get_account(price_account_address(&eth_btc))
+        .await
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,54 @@
+//! Per-feed overrides of the config.
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::signature::Signer;
+
+use common::*;
+use redstone_sol::consumer::price_account_address;
+use redstone_sol::error::RedstoneError;
+
+#[tokio::test]
+async fn test_rejects_out_of_bounds_value() {
+    let (mut context, _) = start().await;
+    let owner = context.payer.pubkey();
+    let feed_id = btc_feed_id();
+
+    let mut max_value = [0u8; 32];
+    max_value[31] = 1;
+    send(
+        &mut context,
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::SetFeedConfig {
+                owner,
+                config_account: pda(&[b"config"]),
+                feed_config: pda(&[b"feed_config", feed_id.as_ref()]),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::SetFeedConfig {
+                feed_id,
+                params: redstone_sol::instructions::FeedConfigParams {
+                    max_value: Some(max_value),
+                    ..Default::default()
+                },
+            }
+            .data(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let err = send(&mut context, process_payload_instruction(owner, vec![]))
+        .await
+        .unwrap_err();
+    assert_error(err, RedstoneError::ValueOutOfBounds);
+    assert!(context
+        .banks_client
+        .get_account(price_account_address(&feed_id))
+        .await
+        .unwrap()
+        .is_none());
+}
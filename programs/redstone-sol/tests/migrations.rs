@@ -0,0 +1,262 @@
+//! Migrates accounts written in the layouts of older versions.
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{
+    AccountDeserialize, Discriminator, InstructionData, ToAccountMetas,
+};
+use solana_sdk::signature::Signer;
+
+use common::*;
+use redstone_sol::constants::{CONFIG_ACCOUNT_VERSION, PRICE_DATA_VERSION};
+use redstone_sol::consumer::price_account_address;
+use redstone_sol::error::RedstoneError;
+use redstone_sol::state::{ConfigAccount, FeedConfig, FeedId, PriceData};
+
+// size_of the config before versioning, allocated with room for 10 more
+// signers
+const LEGACY_CONFIG_ACCOUNT_SIZE: usize = 8 + 80 + 32 * 10;
+// feed id and the first three overrides of the first version
+const LEGACY_FEED_CONFIG_SIZE: usize = 8 + 72;
+
+fn migrate_price_account_instruction(owner: Pubkey) -> Instruction {
+    let feed_id = btc_feed_id();
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::MigratePriceAccount {
+            owner,
+            config_account: pda(&[b"config"]),
+            price_account: price_account_address(&feed_id),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::MigratePriceAccount { feed_id }
+            .data(),
+    }
+}
+
+fn migrate_config_instruction(owner: Pubkey) -> Instruction {
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::MigrateConfig {
+            owner,
+            config_account: pda(&[b"config"]),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::MigrateConfig {}.data(),
+    }
+}
+
+fn migrate_feed_config_instruction(
+    owner: Pubkey,
+    feed_id: FeedId,
+) -> Instruction {
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::MigrateFeedConfig {
+            owner,
+            config_account: pda(&[b"config"]),
+            feed_config: pda(&[b"feed_config", feed_id.as_ref()]),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::MigrateFeedConfig { feed_id }.data(),
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_price_account() {
+    let (mut context, package_timestamp) = start().await;
+    let owner = context.payer.pubkey();
+    let feed_id = btc_feed_id();
+
+    let mut value = [0u8; 32];
+    value[24..].copy_from_slice(&6_000_000_000_000u64.to_be_bytes());
+    let timestamp = package_timestamp - 60_000;
+    let mut data = PriceData::DISCRIMINATOR.to_vec();
+    (feed_id, value, timestamp, timestamp + 1000)
+        .serialize(&mut data)
+        .unwrap();
+    assert_eq!(data.len(), redstone_sol::constants::LEGACY_PRICE_DATA_SIZE);
+    set_program_account(
+        &mut context,
+        &price_account_address(&feed_id),
+        &data,
+    );
+
+    send(&mut context, migrate_price_account_instruction(owner))
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(price_account_address(&feed_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), 8 + std::mem::size_of::<PriceData>());
+    assert!(Rent::default().is_exempt(account.lamports, account.data.len()));
+    let migrated = price_data(&mut context, &feed_id).await;
+    assert_eq!(migrated.feed_id, feed_id);
+    assert_eq!(migrated.value, value);
+    assert_eq!(migrated.timestamp, timestamp);
+    assert_eq!(migrated.write_timestamp, timestamp + 1000);
+    assert_eq!(migrated.version, PRICE_DATA_VERSION);
+    assert_eq!(migrated.decimals, 8);
+    assert_eq!(migrated.round_id, 1);
+
+    next_slot(&mut context).await;
+    let err = send(&mut context, migrate_price_account_instruction(owner))
+        .await
+        .unwrap_err();
+    assert_error(err, RedstoneError::AccountAlreadyMigrated);
+
+    // updated like any other price account
+    send(&mut context, process_payload_instruction(owner, vec![]))
+        .await
+        .unwrap();
+    let updated = price_data(&mut context, &feed_id).await;
+    assert_eq!(updated.timestamp, package_timestamp);
+    assert_eq!(updated.round_id, 2);
+}
+
+#[tokio::test]
+async fn test_migrate_price_account_rejects_unknown_layout() {
+    let (mut context, _) = start().await;
+    let owner = context.payer.pubkey();
+
+    let mut data = PriceData::DISCRIMINATOR.to_vec();
+    data.resize(redstone_sol::constants::LEGACY_PRICE_DATA_SIZE + 2, 0);
+    set_program_account(
+        &mut context,
+        &price_account_address(&btc_feed_id()),
+        &data,
+    );
+
+    let err = send(&mut context, migrate_price_account_instruction(owner))
+        .await
+        .unwrap_err();
+    assert_error(err, RedstoneError::UnsupportedAccountLayout);
+}
+
+#[tokio::test]
+async fn test_migrate_config() {
+    let (mut context, _) = start().await;
+    let owner = context.payer.pubkey();
+    let config_address = pda(&[b"config"]);
+
+    let account = context
+        .banks_client
+        .get_account(config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let config =
+        ConfigAccount::try_deserialize(&mut &account.data[..]).unwrap();
+    let mut data = ConfigAccount::DISCRIMINATOR.to_vec();
+    (
+        config.owner,
+        config.signer_count_threshold,
+        config.signers.clone(),
+        config.max_timestamp_delay_ms,
+        config.max_timestamp_ahead_ms,
+    )
+        .serialize(&mut data)
+        .unwrap();
+    // leftovers of a longer signer list, read as the version byte
+    data.resize(LEGACY_CONFIG_ACCOUNT_SIZE, 0xab);
+    set_program_account(&mut context, &config_address, &data);
+
+    send(&mut context, migrate_config_instruction(owner))
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let space = ConfigAccount::space(config.signers.len());
+    assert_eq!(account.data.len(), space);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(space));
+    let migrated =
+        ConfigAccount::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(migrated.owner, config.owner);
+    assert_eq!(migrated.signers, config.signers);
+    assert_eq!(
+        migrated.signer_count_threshold,
+        config.signer_count_threshold
+    );
+    assert_eq!(
+        migrated.max_timestamp_delay_ms,
+        config.max_timestamp_delay_ms
+    );
+    assert_eq!(
+        migrated.max_timestamp_ahead_ms,
+        config.max_timestamp_ahead_ms
+    );
+    assert_eq!(migrated.version, CONFIG_ACCOUNT_VERSION);
+
+    next_slot(&mut context).await;
+    let err = send(&mut context, migrate_config_instruction(owner))
+        .await
+        .unwrap_err();
+    assert_error(err, RedstoneError::AccountAlreadyMigrated);
+
+    send(&mut context, process_payload_instruction(owner, vec![]))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_migrate_feed_config() {
+    let (mut context, _) = start().await;
+    let owner = context.payer.pubkey();
+    let feed_id = btc_feed_id();
+    let feed_config_address = pda(&[b"feed_config", feed_id.as_ref()]);
+
+    let feed_config = FeedConfig {
+        feed_id,
+        signer_count_threshold: Some(2),
+        max_timestamp_delay_ms: Some(5_000),
+        ..Default::default()
+    };
+    let mut data = vec![0u8; LEGACY_FEED_CONFIG_SIZE];
+    feed_config.try_serialize(&mut &mut data[..]).unwrap();
+    set_program_account(&mut context, &feed_config_address, &data);
+
+    send(
+        &mut context,
+        migrate_feed_config_instruction(owner, feed_id),
+    )
+    .await
+    .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(feed_config_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), FeedConfig::space());
+    assert!(Rent::default().is_exempt(account.lamports, account.data.len()));
+    let migrated =
+        FeedConfig::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(migrated.feed_id, feed_id);
+    assert_eq!(migrated.signer_count_threshold, Some(2));
+    assert_eq!(migrated.max_timestamp_delay_ms, Some(5_000));
+    assert_eq!(migrated.max_timestamp_ahead_ms, None);
+    assert_eq!(migrated.decimals, None);
+
+    next_slot(&mut context).await;
+    let err = send(
+        &mut context,
+        migrate_feed_config_instruction(owner, feed_id),
+    )
+    .await
+    .unwrap_err();
+    assert_error(err, RedstoneError::AccountAlreadyMigrated);
+}
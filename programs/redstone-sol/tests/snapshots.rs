@@ -0,0 +1,229 @@
+//! Snapshots of a feed group written from a single payload.
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::Signer;
+
+use common::*;
+use redstone_sol::error::RedstoneError;
+use redstone_sol::state::{FeedId, PriceSnapshot};
+
+fn create_snapshot_instruction(
+    owner: Pubkey,
+    group_id: FeedId,
+    feed_ids: Vec<FeedId>,
+) -> Instruction {
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::CreateSnapshot {
+            owner,
+            config_account: pda(&[b"config"]),
+            feed_registry: pda(&[b"registry"]),
+            snapshot: pda(&[b"snapshot", group_id.as_ref()]),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::CreateSnapshot {
+            group_id,
+            feed_ids,
+        }
+        .data(),
+    }
+}
+
+/// Pushes `payload` into the snapshot of `group_id`, with the feed configs
+/// of `feed_ids`
+fn process_snapshot_instruction(
+    owner: Pubkey,
+    group_id: FeedId,
+    feed_ids: &[FeedId],
+    payload: Vec<u8>,
+) -> Instruction {
+    let mut accounts = redstone_sol::accounts::ProcessSnapshotPayload {
+        user: owner,
+        config_account: pda(&[b"config"]),
+        feed_registry: pda(&[b"registry"]),
+        snapshot: pda(&[b"snapshot", group_id.as_ref()]),
+    }
+    .to_account_metas(None);
+    for feed_id in feed_ids {
+        accounts.push(AccountMeta::new_readonly(
+            pda(&[b"feed_config", feed_id.as_ref()]),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: redstone_sol::ID,
+        accounts,
+        data: redstone_sol::instruction::ProcessSnapshotPayload {
+            group_id,
+            payload,
+        }
+        .data(),
+    }
+}
+
+async fn snapshot(
+    context: &mut ProgramTestContext,
+    group_id: &FeedId,
+) -> PriceSnapshot {
+    let account = context
+        .banks_client
+        .get_account(pda(&[b"snapshot", group_id.as_ref()]))
+        .await
+        .unwrap()
+        .unwrap();
+    PriceSnapshot::try_deserialize(&mut &account.data[..]).unwrap()
+}
+
+#[tokio::test]
+async fn test_snapshot() {
+    let (mut context, package_timestamp) = setup().await;
+    let owner = context.payer.pubkey();
+    let group_id: FeedId = "MAJORS".parse().unwrap();
+    let feed_id = btc_feed_id();
+
+    send(
+        &mut context,
+        create_snapshot_instruction(owner, group_id, vec![feed_id]),
+    )
+    .await
+    .unwrap();
+    send(
+        &mut context,
+        process_snapshot_instruction(
+            owner,
+            group_id,
+            &[feed_id],
+            PAYLOAD.to_vec(),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let snapshot = snapshot(&mut context, &group_id).await;
+    let price_data = price_data(&mut context, &feed_id).await;
+    assert_eq!(snapshot.timestamp, package_timestamp);
+    assert_eq!(snapshot.round_id, 1);
+    assert_eq!(snapshot.entries[0].feed_id, feed_id);
+    assert_eq!(snapshot.entries[0].value, price_data.value);
+    assert_eq!(snapshot.entries[0].signer_count, 3);
+
+    // the same payload is not newer than the snapshot
+    next_slot(&mut context).await;
+    let err = send(
+        &mut context,
+        process_snapshot_instruction(
+            owner,
+            group_id,
+            &[feed_id],
+            PAYLOAD.to_vec(),
+        ),
+    )
+    .await
+    .unwrap_err();
+    assert_error(err, RedstoneError::TimestampTooOld);
+}
+
+#[tokio::test]
+async fn test_snapshot_uses_feed_config_timestamp_limits() {
+    let (mut context, package_timestamp) = setup().await;
+    let owner = context.payer.pubkey();
+    let group_id: FeedId = "MAJORS".parse().unwrap();
+    let feed_id = btc_feed_id();
+
+    let instructions = [
+        create_snapshot_instruction(owner, group_id, vec![feed_id]),
+        // far stricter than the global 15 minutes
+        Instruction {
+            program_id: redstone_sol::ID,
+            accounts: redstone_sol::accounts::SetFeedConfig {
+                owner,
+                config_account: pda(&[b"config"]),
+                feed_config: pda(&[b"feed_config", feed_id.as_ref()]),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: redstone_sol::instruction::SetFeedConfig {
+                feed_id,
+                params: redstone_sol::instructions::FeedConfigParams {
+                    max_timestamp_delay_ms: Some(1_000),
+                    ..Default::default()
+                },
+            }
+            .data(),
+        },
+    ];
+    for instruction in instructions {
+        send(&mut context, instruction).await.unwrap();
+    }
+
+    set_clock_ms(&mut context, package_timestamp + 5_000).await;
+    let err = send(
+        &mut context,
+        process_snapshot_instruction(
+            owner,
+            group_id,
+            &[feed_id],
+            PAYLOAD.to_vec(),
+        ),
+    )
+    .await
+    .unwrap_err();
+    assert_error(err, RedstoneError::TimestampTooOld);
+}
+
+#[tokio::test]
+async fn test_snapshot_of_several_feeds() {
+    let keys = signer_keys();
+    let timestamp = 1_700_000_000_000;
+    let mut context = start_with_signers(
+        keys.iter().map(signer_address).collect(),
+        timestamp,
+    )
+    .await;
+    let owner = context.payer.pubkey();
+    let group_id: FeedId = "MAJORS".parse().unwrap();
+    let btc = btc_feed_id();
+    let eth: FeedId = "ETH".parse().unwrap();
+
+    send(&mut context, register_feed_instruction(owner, &eth))
+        .await
+        .unwrap();
+    send(
+        &mut context,
+        create_snapshot_instruction(owner, group_id, vec![btc, eth]),
+    )
+    .await
+    .unwrap();
+    // one package per feed and signer, the ETH ones first
+    let payload = signed_payload(
+        &keys,
+        timestamp,
+        &[(eth, 250_000_000_000), (btc, 6_000_000_000_000)],
+    );
+    send(
+        &mut context,
+        process_snapshot_instruction(owner, group_id, &[btc, eth], payload),
+    )
+    .await
+    .unwrap();
+
+    let snapshot = snapshot(&mut context, &group_id).await;
+    assert_eq!(snapshot.timestamp, timestamp);
+    assert_eq!(snapshot.round_id, 1);
+    for (entry, (feed_id, value)) in snapshot
+        .entries
+        .iter()
+        .zip([(btc, 6_000_000_000_000u64), (eth, 250_000_000_000)])
+    {
+        let mut expected = [0u8; 32];
+        expected[24..].copy_from_slice(&value.to_be_bytes());
+        assert_eq!(entry.feed_id, feed_id);
+        assert_eq!(entry.value, expected);
+        assert_eq!(entry.signer_count, 3);
+    }
+}
@@ -0,0 +1,68 @@
+//! Read-only views returning prices through the return data.
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use common::*;
+use redstone_sol::consumer::price_account_address;
+use redstone_sol::state::{FeedId, PriceEntry, PriceStatus, RoundData};
+
+const MAX_AGE_MS: u64 = 60_000;
+
+#[tokio::test]
+async fn test_latest_round_data() {
+    let (mut context, package_timestamp) = setup().await;
+
+    let feed_id = btc_feed_id();
+    let instruction = Instruction {
+        program_id: redstone_sol::ID,
+        accounts: redstone_sol::accounts::LatestRoundData {
+            price_account: price_account_address(&feed_id),
+        }
+        .to_account_metas(None),
+        data: redstone_sol::instruction::LatestRoundData { feed_id }.data(),
+    };
+    let return_data = simulate_return_data(&mut context, instruction).await;
+
+    let round_data = RoundData::try_from_slice(&return_data).unwrap();
+    assert_eq!(round_data.round_id, 1);
+    assert_eq!(round_data.answered_in_round, 1);
+    assert_eq!(round_data.started_at, package_timestamp);
+    assert!(round_data.answer.iter().any(|&b| b != 0));
+}
+
+#[tokio::test]
+async fn test_get_prices() {
+    let (mut context, package_timestamp) = setup().await;
+
+    let btc = btc_feed_id();
+    let eth: FeedId = "ETH".parse().unwrap();
+    let instruction = Instruction {
+        program_id: redstone_sol::ID,
+        accounts: [btc, eth]
+            .iter()
+            .map(|feed_id| {
+                AccountMeta::new_readonly(
+                    price_account_address(feed_id),
+                    false,
+                )
+            })
+            .collect(),
+        data: redstone_sol::instruction::GetPrices {
+            feed_ids: vec![btc, eth],
+            max_age_ms: Some(MAX_AGE_MS),
+        }
+        .data(),
+    };
+    let return_data = simulate_return_data(&mut context, instruction).await;
+
+    let entries = Vec::<PriceEntry>::try_from_slice(&return_data).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].feed_id, btc);
+    assert_eq!(entries[0].timestamp, package_timestamp);
+    assert_eq!(entries[0].status, PriceStatus::Ok);
+    assert_eq!(entries[1].feed_id, eth);
+    assert_eq!(entries[1].status, PriceStatus::Missing);
+}
@@ -26,7 +26,11 @@ async function main() {
   console.log("Pushing data in a loop");
   while (true) {
     try {
-      const transaction = await makeTransaction(signer, options.feedId);
+      const transaction = await makeTransaction(
+        signer,
+        options.feedId,
+        connection,
+      );
       const signature = await sendTransaction(connection, transaction, signer);
       console.log(`${Date.now()}: ${signature}`);
     } catch (error) {
@@ -5,6 +5,9 @@ import { readFile } from "fs/promises";
 
 export const SYSTEM_PROGRAM_ID = "11111111111111111111111111111111";
 export const METHOD_DISCRIMINATOR = [49, 96, 127, 141, 118, 203, 237, 178];
+export const DERIVED_FEED_DISCRIMINATOR = [107, 96, 137, 58, 100, 193, 240, 249];
+// derived feeds change rarely, they are looked up again this often
+export const DERIVED_FEEDS_REFRESH_MS = 10 * 60 * 1000;
 export const REDSTONE_SOL_PROGRAM_ID =
   "3oHtb7BCqjqhZt8LyqSAZRAubbrYy8xvDRaYoRghHB1T";
 export const DATA_SERVICE_ID = "redstone-primary-prod";
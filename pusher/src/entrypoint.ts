@@ -23,7 +23,7 @@ async function pushData() {
 
     console.log("Using signer:", signer.publicKey.toBase58());
     try {
-      const transaction = await makeTransaction(signer, FEED_ID);
+      const transaction = await makeTransaction(signer, FEED_ID, connection);
       const signature = await sendTransactionWithJito(
         connection,
         transaction,
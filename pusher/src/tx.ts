@@ -1,6 +1,7 @@
 import bs58 from "bs58";
 import { requestRedstonePayload } from "@redstone-finance/sdk";
 import {
+  AccountMeta,
   Connection,
   Keypair,
   PublicKey,
@@ -14,6 +15,8 @@ import {
   DATA_SERVICE_ID,
   UNIQUE_SIGNER_COUNT,
   METHOD_DISCRIMINATOR,
+  DERIVED_FEED_DISCRIMINATOR,
+  DERIVED_FEEDS_REFRESH_MS,
 } from "./config.js";
 import { makeFeedIdBytes, makePriceSeed } from "./util.js";
 
@@ -38,22 +41,30 @@ async function retry<T>(
   throw lastError;
 }
 
+// With a connection, the derived feeds of `feedId` are recomputed as well
 export async function makeTransaction(
   signer: Keypair,
   feedId: string,
+  connection?: Connection,
 ): Promise<Transaction> {
   const priceAccount = getPriceAccount(feedId);
   const configAccount = getConfigAccount();
-  const keys = [
+  const feedConfigAccount = getFeedConfigAccount(feedId);
+  const keys: AccountMeta[] = [
     { pubkey: signer.publicKey, isSigner: true, isWritable: true },
     { pubkey: priceAccount, isSigner: false, isWritable: true },
     { pubkey: configAccount, isSigner: false, isWritable: false },
+    { pubkey: feedConfigAccount, isSigner: false, isWritable: false },
+    { pubkey: getFeedRegistryAccount(), isSigner: false, isWritable: false },
     {
       pubkey: new PublicKey(SYSTEM_PROGRAM_ID),
       isSigner: false,
       isWritable: false,
     },
   ];
+  if (connection) {
+    keys.push(...(await getCachedDerivedFeedAccounts(connection, feedId)));
+  }
 
   const instructionData = await makeInstructionData(feedId);
 
@@ -136,8 +147,88 @@ async function makePayload(dataFeeds: string[]): Promise<Uint8Array> {
   return Uint8Array.from(JSON.parse(res));
 }
 
+const derivedFeedAccounts = new Map<
+  string,
+  { keys: AccountMeta[]; fetchedAt: number }
+>();
+
+// `getDerivedFeedAccounts` behind a cache refreshed every
+// `DERIVED_FEEDS_REFRESH_MS`, as `getProgramAccounts` is expensive and
+// often rate-limited. A failed refresh keeps the cached accounts
+async function getCachedDerivedFeedAccounts(
+  connection: Connection,
+  feedId: string,
+): Promise<AccountMeta[]> {
+  const cached = derivedFeedAccounts.get(feedId);
+  if (cached && Date.now() - cached.fetchedAt < DERIVED_FEEDS_REFRESH_MS) {
+    return cached.keys;
+  }
+  try {
+    const keys = await getDerivedFeedAccounts(connection, feedId);
+    derivedFeedAccounts.set(feedId, { keys, fetchedAt: Date.now() });
+    return keys;
+  } catch (error) {
+    if (!cached) {
+      throw error;
+    }
+    console.error("Derived feed lookup failed, using cached:", error);
+    return cached.keys;
+  }
+}
+
+// `[derived feed, derived price account, other source price account]`
+// triples of the derived feeds with `feedId` as the numerator or denominator
+export async function getDerivedFeedAccounts(
+  connection: Connection,
+  feedId: string,
+): Promise<AccountMeta[]> {
+  const keys: AccountMeta[] = [];
+  // DerivedFeed: discriminator, feed id, numerator, denominator, decimals
+  for (const [offset, otherOffset] of [
+    [40, 72],
+    [72, 40],
+  ]) {
+    const derivedFeeds = await connection.getProgramAccounts(
+      new PublicKey(REDSTONE_SOL_PROGRAM_ID),
+      {
+        filters: [
+          {
+            memcmp: {
+              offset: 0,
+              bytes: bs58.encode(Uint8Array.from(DERIVED_FEED_DISCRIMINATOR)),
+            },
+          },
+          { memcmp: { offset, bytes: bs58.encode(makeFeedIdBytes(feedId)) } },
+        ],
+      },
+    );
+    for (const { pubkey, account } of derivedFeeds) {
+      keys.push(
+        { pubkey, isSigner: false, isWritable: false },
+        {
+          pubkey: getPriceAccountByBytes(account.data.subarray(8, 40)),
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: getPriceAccountByBytes(
+            account.data.subarray(otherOffset, otherOffset + 32),
+          ),
+          isSigner: false,
+          isWritable: false,
+        },
+      );
+    }
+  }
+  return keys;
+}
+
 function getPriceAccount(feedId: string): PublicKey {
-  const seeds = [makePriceSeed(), makeFeedIdBytes(feedId)];
+  return getPriceAccountByBytes(makeFeedIdBytes(feedId));
+}
+
+function getPriceAccountByBytes(feedIdBytes: Uint8Array): PublicKey {
+  const seeds = [makePriceSeed(), feedIdBytes];
   const [priceAccount] = PublicKey.findProgramAddressSync(
     seeds,
     new PublicKey(REDSTONE_SOL_PROGRAM_ID),
@@ -152,3 +243,19 @@ function getConfigAccount(): PublicKey {
   );
   return configAccount;
 }
+
+function getFeedConfigAccount(feedId: string): PublicKey {
+  const [feedConfigAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from("feed_config"), makeFeedIdBytes(feedId)],
+    new PublicKey(REDSTONE_SOL_PROGRAM_ID),
+  );
+  return feedConfigAccount;
+}
+
+function getFeedRegistryAccount(): PublicKey {
+  const [feedRegistryAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from("registry")],
+    new PublicKey(REDSTONE_SOL_PROGRAM_ID),
+  );
+  return feedRegistryAccount;
+}
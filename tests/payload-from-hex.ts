@@ -27,6 +27,8 @@ describe.skip("redstone-sol", () => {
 
   let configAccount: anchor.web3.PublicKey;
 
+  let feedConfig: anchor.web3.PublicKey;
+
   const systemProgram = anchor.web3.SystemProgram.programId;
 
   before(async () => {
@@ -39,6 +41,11 @@ describe.skip("redstone-sol", () => {
       [Buffer.from("config")],
       program.programId
     )[0];
+
+    feedConfig = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("feed_config"), makeFeedIdBytes(feedId)],
+      program.programId
+    )[0];
   });
 
   it("initializes correctly", async () => {
@@ -89,7 +96,7 @@ describe.skip("redstone-sol", () => {
 
     const feedIdBytes = makeFeedIdBytes(feedId);
     const tx = await program.methods
-      .processRedstonePayload(Array.from(feedIdBytes), Buffer.from(payload))
+      .processRedstonePayload([Array.from(feedIdBytes)], Buffer.from(payload))
       .preInstructions([
         ComputeBudgetProgram.setComputeUnitLimit({ units: 1_000_000 }),
       ])
@@ -97,6 +104,11 @@ describe.skip("redstone-sol", () => {
         user: provider.wallet.publicKey,
         priceAccount,
         configAccount,
+        feedConfig,
+        feedRegistry: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("registry")],
+          program.programId
+        )[0],
         systemProgram,
       })
       .rpc({ skipPreflight: true });
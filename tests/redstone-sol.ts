@@ -8,6 +8,7 @@ import {
   makePriceSeed,
   makeFeedIdBytes,
   deserializePriceData,
+  emptyFeedConfigParams,
 } from "./util";
 import { PRIMARY_SIGNERS } from "../migrations/signers";
 
@@ -33,22 +34,47 @@ describe("redstone-sol", () => {
 
   let pdas = {};
 
+  let feedConfigPdas = {};
+
   let configAccount: anchor.web3.PublicKey;
 
+  let feedRegistry: anchor.web3.PublicKey;
+
   const systemProgram = anchor.web3.SystemProgram.programId;
 
+  // register_feed checks that no derived or basket feed uses the id
+  const compositeFeedPdas = (feedId: string) => ({
+    derivedFeed: anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("derived_feed"), makeFeedIdBytes(feedId)],
+      program.programId
+    )[0],
+    basketFeed: anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("basket_feed"), makeFeedIdBytes(feedId)],
+      program.programId
+    )[0],
+  });
+
   before(async () => {
     for (const feedId of feedIds) {
       pdas[feedId] = anchor.web3.PublicKey.findProgramAddressSync(
         [makePriceSeed(), makeFeedIdBytes(feedId)],
         program.programId
       )[0];
+      feedConfigPdas[feedId] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("feed_config"), makeFeedIdBytes(feedId)],
+        program.programId
+      )[0];
     }
 
     configAccount = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("config")],
       program.programId
     )[0];
+
+    feedRegistry = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("registry")],
+      program.programId
+    )[0];
   });
 
   it("initializes correctly", async () => {
@@ -69,17 +95,94 @@ describe("redstone-sol", () => {
     console.log("Config initialized at:", configAccount.toString());
   });
 
+  it("registers the feeds", async () => {
+    await program.methods
+      .initializeFeedRegistry()
+      .accountsStrict({
+        owner: provider.wallet.publicKey,
+        configAccount,
+        feedRegistry,
+        systemProgram,
+      })
+      .rpc();
+
+    for (const feedId of feedIds) {
+      await program.methods
+        .registerFeed(feedId)
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          feedRegistry,
+          ...compositeFeedPdas(feedId),
+          systemProgram,
+        })
+        .rpc();
+    }
+
+    const registry = await program.account.feedRegistry.fetch(feedRegistry);
+    expect(registry.feeds.length).to.equal(feedIds.length);
+  });
+
+  it("rejects invalid feed names", async () => {
+    try {
+      await program.methods
+        .registerFeed("BTC USD")
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          feedRegistry,
+          ...compositeFeedPdas("BTC USD"),
+          systemProgram,
+        })
+        .rpc();
+      expect.fail("Expected error but transaction succeeded");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidFeedId");
+    }
+  });
+
+  it("rejects updates for unregistered feeds", async () => {
+    const feedId = "ARB";
+    try {
+      await program.methods
+        .processRedstonePayload(
+          [Array.from(makeFeedIdBytes(feedId))],
+          await makePayload([feedId])
+        )
+        .accountsStrict({
+          user: provider.wallet.publicKey,
+          priceAccount: anchor.web3.PublicKey.findProgramAddressSync(
+            [makePriceSeed(), makeFeedIdBytes(feedId)],
+            program.programId
+          )[0],
+          configAccount,
+          feedConfig: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("feed_config"), makeFeedIdBytes(feedId)],
+            program.programId
+          )[0],
+          feedRegistry,
+          systemProgram,
+        })
+        .rpc();
+      expect.fail("Expected error but transaction succeeded");
+    } catch (error) {
+      expect(error.toString()).to.include("FeedNotRegistered");
+    }
+  });
+
   async function testFeedIdPush(feedId: string) {
     it(`Updates correctly for ${feedId} feed`, async () => {
       const payload = await makePayload([feedId]);
       const feedIdBytes = makeFeedIdBytes(feedId);
       const priceAccount = pdas[feedId];
       const tx = await program.methods
-        .processRedstonePayload(Array.from(feedIdBytes), payload)
+        .processRedstonePayload([Array.from(feedIdBytes)], payload)
         .accountsStrict({
           user: provider.wallet.publicKey,
           priceAccount,
           configAccount,
+          feedConfig: feedConfigPdas[feedId],
+          feedRegistry,
           systemProgram,
         })
         .rpc({ skipPreflight: true });
@@ -92,6 +195,15 @@ describe("redstone-sol", () => {
 
       expect(priceAccountData.feedId).to.equal(feedId);
       expect(priceAccountData.value).to.not.equal("0");
+      expect(priceAccountData.signerCount).to.be.greaterThanOrEqual(3);
+      expect(priceAccountData.signerCount).to.equal(
+        priceAccountData.signerBitmap.toString(2).split("1").length - 1
+      );
+      const value = BigInt(priceAccountData.value);
+      expect(BigInt(priceAccountData.lowestValue) <= value).to.be.true;
+      expect(BigInt(priceAccountData.highestValue) >= value).to.be.true;
+      expect(priceAccountData.writeSlot).to.not.equal("0");
+      expect(priceAccountData.roundId).to.equal("1");
 
       console.log(`${feedId}: ${JSON.stringify(priceAccountData)}`);
     });
@@ -112,6 +224,7 @@ describe("redstone-sol", () => {
         .accountsStrict({
           owner: provider.wallet.publicKey,
           configAccount,
+          systemProgram,
         })
         .rpc();
 
@@ -142,6 +255,7 @@ describe("redstone-sol", () => {
           .accountsStrict({
             owner: nonOwnerWallet.publicKey,
             configAccount,
+            systemProgram,
           })
           .signers([nonOwnerWallet])
           .rpc();
@@ -164,6 +278,7 @@ describe("redstone-sol", () => {
         .accountsStrict({
           owner: provider.wallet.publicKey,
           configAccount,
+          systemProgram,
         })
         .rpc();
 
@@ -182,5 +297,173 @@ describe("redstone-sol", () => {
         originalConfig.maxTimestampAheadMs.toNumber()
       );
     });
+
+    it("Config account is resized to the signer list", async () => {
+      const originalConfig = await program.account.configAccount.fetch(
+        configAccount
+      );
+      const manySigners = Array.from({ length: 20 }, () =>
+        Array.from(anchor.web3.Keypair.generate().publicKey.toBytes()).slice(
+          0,
+          20
+        )
+      );
+
+      const updateSigners = (signers: number[][]) =>
+        program.methods
+          .updateConfig(signers, null, null, null)
+          .accountsStrict({
+            owner: provider.wallet.publicKey,
+            configAccount,
+            systemProgram,
+          })
+          .rpc();
+
+      // 8 discriminator + 32 owner + 1 threshold + 4 vec length
+      // + 20 per signer + 8 + 8 timestamps + 1 version
+      const configSize = (signerCount: number) => 62 + 20 * signerCount;
+
+      await updateSigners(manySigners);
+      expect(
+        (await provider.connection.getAccountInfo(configAccount)).data.length
+      ).to.equal(configSize(20));
+
+      await updateSigners(originalConfig.signers);
+      expect(
+        (await provider.connection.getAccountInfo(configAccount)).data.length
+      ).to.equal(configSize(originalConfig.signers.length));
+    });
+
+    it("Owner can set a feed config", async () => {
+      const feedConfig = feedConfigPdas["BTC"];
+
+      await program.methods
+        .setFeedConfig([Array.from(makeFeedIdBytes("BTC"))], {
+          ...emptyFeedConfigParams,
+          signerCountThreshold: 5,
+          maxTimestampAheadMs: new anchor.BN(60 * 1000),
+          decimals: 8,
+          aggregation: { lowerMedian: {} },
+          outlierFilter: { band: { maxDeviationBps: new anchor.BN(1_000) } },
+          maxPackageTimestampSkewMs: new anchor.BN(1_000),
+          timestampAggregation: { median: {} },
+          minUpdateIntervalMs: new anchor.BN(30 * 1000),
+          updateDeviationBps: new anchor.BN(50),
+        })
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          feedConfig,
+          systemProgram,
+        })
+        .rpc();
+
+      const feedConfigData = await program.account.feedConfig.fetch(
+        feedConfig
+      );
+
+      expect(feedConfigData.signerCountThreshold).to.equal(5);
+      expect(feedConfigData.maxTimestampDelayMs).to.be.null;
+      expect(feedConfigData.maxTimestampAheadMs.toNumber()).to.equal(
+        60 * 1000
+      );
+      expect(feedConfigData.decimals).to.equal(8);
+      expect(feedConfigData.aggregation).to.deep.equal({ lowerMedian: {} });
+      expect(
+        feedConfigData.outlierFilter.band.maxDeviationBps.toNumber()
+      ).to.equal(1_000);
+      expect(feedConfigData.maxPackageTimestampSkewMs.toNumber()).to.equal(
+        1_000
+      );
+      expect(feedConfigData.timestampAggregation).to.deep.equal({ median: {} });
+      expect(feedConfigData.minUpdateIntervalMs.toNumber()).to.equal(30 * 1000);
+      expect(feedConfigData.updateDeviationBps.toNumber()).to.equal(50);
+    });
+
+    it("Skips updates not newer than the stored price when enabled", async () => {
+      const feedId = "ETH";
+      const feedIdArg = [Array.from(makeFeedIdBytes(feedId))];
+      await program.methods
+        .setFeedConfig(feedIdArg, {
+          ...emptyFeedConfigParams,
+          skipIfNotNewer: true,
+        })
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          feedConfig: feedConfigPdas[feedId],
+          systemProgram,
+        })
+        .rpc();
+
+      const payload = await makePayload([feedId]);
+      const push = () =>
+        program.methods
+          .processRedstonePayload(feedIdArg, payload)
+          .accountsStrict({
+            user: provider.wallet.publicKey,
+            priceAccount: pdas[feedId],
+            configAccount,
+            feedConfig: feedConfigPdas[feedId],
+            feedRegistry,
+            systemProgram,
+          })
+          .rpc();
+      const readPriceData = async () =>
+        deserializePriceData(
+          (await provider.connection.getAccountInfo(pdas[feedId])).data
+        );
+
+      // the payload may already be stored by the regular update test
+      await push();
+      const stored = await readPriceData();
+      await push();
+      expect(await readPriceData()).to.deep.equal(stored);
+    });
+  });
+
+  describe("Price account closing", () => {
+    const feedId = "EUR";
+    const destination = anchor.web3.Keypair.generate().publicKey;
+
+    const closePriceAccount = () =>
+      program.methods
+        .closePriceAccount([Array.from(makeFeedIdBytes(feedId))])
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          feedRegistry,
+          priceAccount: pdas[feedId],
+          ...compositeFeedPdas(feedId),
+          destination,
+        })
+        .rpc();
+
+    it("Active feeds cannot be closed", async () => {
+      try {
+        await closePriceAccount();
+        expect.fail("Expected error but transaction succeeded");
+      } catch (error) {
+        expect(error.toString()).to.include("FeedStillActive");
+      }
+    });
+
+    it("Owner can close a deactivated feed", async () => {
+      await program.methods
+        .setFeedActive([Array.from(makeFeedIdBytes(feedId))], false)
+        .accountsStrict({
+          owner: provider.wallet.publicKey,
+          configAccount,
+          feedRegistry,
+        })
+        .rpc();
+
+      const rent = await provider.connection.getBalance(pdas[feedId]);
+      await closePriceAccount();
+
+      expect(await provider.connection.getAccountInfo(pdas[feedId])).to.be
+        .null;
+      expect(await provider.connection.getBalance(destination)).to.equal(rent);
+    });
   });
 });
@@ -6,6 +6,17 @@ export interface PriceData {
   value: string;
   timestamp: string;
   writeTimestamp: string;
+  maxJumpBps: number;
+  pendingValue: string;
+  pendingTimestamp: string;
+  version: number;
+  decimals: number;
+  signerBitmap: number;
+  signerCount: number;
+  lowestValue: string;
+  highestValue: string;
+  writeSlot: string;
+  roundId: string;
 }
 
 export const makeFeedIdBytes = (feedId: string) => {
@@ -16,6 +27,23 @@ export const makePriceSeed = () => {
   return Buffer.from("price".padEnd(32, "\0"));
 };
 
+// set_feed_config params with every override unset
+export const emptyFeedConfigParams = {
+  signerCountThreshold: null,
+  maxTimestampDelayMs: null,
+  maxTimestampAheadMs: null,
+  decimals: null,
+  minValue: null,
+  maxValue: null,
+  aggregation: null,
+  outlierFilter: null,
+  maxPackageTimestampSkewMs: null,
+  timestampAggregation: null,
+  skipIfNotNewer: null,
+  minUpdateIntervalMs: null,
+  updateDeviationBps: null,
+};
+
 export const makePayload = async (dataPackagesIds: Array<string>) => {
   const DATA_SERVICE_ID = "redstone-primary-prod";
   const UNIQUE_SIGNER_COUNT = 3;
@@ -37,8 +65,11 @@ export const makePayload = async (dataPackagesIds: Array<string>) => {
 };
 
 export const deserializePriceData = (data: Buffer): PriceData => {
-  if (data.length !== 88) {
-    // 8 discriminator + 32 feed id + 32 value + 8 unix + 8 unix
+  if (data.length !== 224) {
+    // 8 discriminator + 216 PriceData: 32 feed id + 32 value + 8 unix + 8 unix
+    // + 32 pending value + 8 unix + 2 bps + 1 version + 1 decimals
+    // + 4 signer bitmap + 1 signer count + 7 reserved
+    // + 32 lowest value + 32 highest value + 8 slot + 8 round id
     throw new Error("Invalid data length for PriceData " + data.length);
   }
 
@@ -48,12 +79,34 @@ export const deserializePriceData = (data: Buffer): PriceData => {
   const value = BigInt(`0x${valueBuffer.toString("hex")}`);
   const timestamp = data.readBigUInt64LE(72);
   const writeTimestamp = data.readBigUInt64LE(80);
+  const pendingValue = BigInt(`0x${data.subarray(88, 120).toString("hex")}`);
+  const pendingTimestamp = data.readBigUInt64LE(120);
+  const maxJumpBps = data.readUInt16LE(128);
+  const version = data.readUInt8(130);
+  const decimals = data.readUInt8(131);
+  const signerBitmap = data.readUInt32LE(132);
+  const signerCount = data.readUInt8(136);
+  const lowestValue = BigInt(`0x${data.subarray(144, 176).toString("hex")}`);
+  const highestValue = BigInt(`0x${data.subarray(176, 208).toString("hex")}`);
+  const writeSlot = data.readBigUInt64LE(208);
+  const roundId = data.readBigUInt64LE(216);
 
   return {
     feedId: feedId,
     value: value.toString(),
     timestamp: timestamp.toString(),
     writeTimestamp: writeTimestamp.toString(),
+    maxJumpBps,
+    pendingValue: pendingValue.toString(),
+    pendingTimestamp: pendingTimestamp.toString(),
+    version,
+    decimals,
+    signerBitmap,
+    signerCount,
+    lowestValue: lowestValue.toString(),
+    highestValue: highestValue.toString(),
+    writeSlot: writeSlot.toString(),
+    roundId: roundId.toString(),
   };
 };
 